@@ -261,6 +261,7 @@ impl DailyYieldWindow {
                         for (log, _) in logs {
                             lfh.push_from_file(&log);
                         }
+                        lfh.finalize();
                         lfh.update();
 
                         if !lfh.is_empty() {
@@ -54,8 +54,11 @@ const SAVE: usize = 10;
 const LIMIT_W:  usize = 11;
 const LIMIT_W2:  usize = 12;
 const EXPORT_FINAL_ONLY: usize = 13;
+const EXPORT_CANONICAL: usize = 14;
+const EXPORT_MODE_FTO_LIM: usize = 15;
+const EXPORT_FINAL_PANEL: usize = 16;
 
-const MESSAGE_E: [[&str;2];14] = [
+const MESSAGE_E: [[&str;2];17] = [
     ["💾 Export",                  "💾 Export"],
     ["Beállítások:",            "Settings:"],
     ["Vertikális elrendezés (1 sor = 1 log/pcb)",   "Vertical orientation (1 row = 1 log/pcb)"],
@@ -65,12 +68,15 @@ const MESSAGE_E: [[&str;2];14] = [
     ["Csak a bukó teszteket",   "Only the failed tests"],
     ["Kézi tesztmegadás",       "Maunaly specify"],
     ["Kiválasztott tesztek:",    "Selected tests:"],
-    ["Egy szóközzel válassza el a kívánt teszteket: Példa: \"c613 r412 v605%ON\"", 
-                                "Separate tests with a space. Example: \"c613 r412 v605%ON\""],
+    ["Egy szóközzel válassza el a kívánt teszteket, a * tetszőleges szöveget helyettesít. Példa: \"c613 r412 v605%ON PS_Info_*\"", 
+                                "Separate tests with a space, * matches any text. Example: \"c613 r412 v605%ON PS_Info_*\""],
     ["Mentés",                  "Save"],
     ["Figyelmeztetés: teszt",                                   "Warning: test"],
     ["limitje változott! Ez a táblázatban nem lesz látható!",   "has limit changes! This won't be visile in the spreadsheet!"],
     ["Csak a végső logok exportálása",   "Export only the final logs"],
+    ["Tesztek exportálása a tesztprogram sorrendjében",   "Export tests in testplan order"],
+    ["Bukó és limitváltozásos tesztek",   "Failed tests and limit changes"],
+    ["Végső log a multiboard utolsó tesztje alapján",   "Final log by the last test of the multiboard"],
 ];
 
 // HOURLY + MULTIBOARDS:
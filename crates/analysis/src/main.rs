@@ -252,6 +252,7 @@ struct AutoUpdate {
     last_log: Option<DateTime<Local>>,
     update_start_time: Option<DateTime<Local>>,
     last_scan_time: Option<DateTime<Local>>,
+    interval: i64, // Seconds between scans
 
     log_buffer: Arc<RwLock<Vec<PathAndTime>>>,
 }
@@ -262,6 +263,11 @@ struct AutoUpdate {
 
 impl AutoUpdate {
     fn default() -> Self {
+        let interval = match Config::read(CONFIG) {
+            Ok(config) => config.get_auto_update_secs() as i64,
+            Err(_) => 30,
+        };
+
         AutoUpdate {
             usable: false,
             enabled: false,
@@ -271,6 +277,7 @@ impl AutoUpdate {
             last_log: None,
             update_start_time: None,
             last_scan_time: None,
+            interval,
 
             log_buffer: Arc::new(RwLock::new(Vec::new())),
         }
@@ -298,7 +305,7 @@ impl AutoUpdate {
 
         if self.enabled && *self.state.read().unwrap() == AUState::Standby {
             if let Some(t) = self.last_scan_time {
-                return (Local::now() - t).num_seconds() > 30;
+                return (Local::now() - t).num_seconds() > self.interval;
             }
         }
 
@@ -383,6 +390,7 @@ struct MyApp {
     time_end: NaiveTime,
     time_end_string: String,
     time_end_use: bool,
+    shift_starts: [u32; 3],
 
     auto_update: AutoUpdate,
 
@@ -409,6 +417,8 @@ struct MyApp {
     selected_test_buf: String,
     selected_test_index: usize,
     selected_test_show_stats: bool,
+    selected_test_stats_gs: bool,
+    selected_test_log_scale: bool,
     selected_test_results: (TType, Vec<(u64, usize, TResult, TLimit)>),
     selected_test_statistics: TestStats,
 
@@ -426,6 +436,11 @@ impl Default for MyApp {
 
         let product_list = load_product_list(PRODUCT_LIST, false);
 
+        let shift_starts = match Config::read(CONFIG) {
+            Ok(config) => config.get_shift_starts(),
+            Err(_) => DEFAULT_SHIFT_STARTS,
+        };
+
         let path_list: Vec<PathBuf> = product_list
             .iter()
             .map(|f| f.get_log_dir().clone())
@@ -444,6 +459,7 @@ impl Default for MyApp {
             time_start,
             time_start_string: time_start.format("%H:%M:%S").to_string(),
             time_end,
+            shift_starts,
             time_end_string: time_end.format("%H:%M:%S").to_string(),
             time_end_use: false,
 
@@ -471,6 +487,8 @@ impl Default for MyApp {
             selected_test_buf: String::new(),
             selected_test_index: 0,
             selected_test_show_stats: false,
+            selected_test_stats_gs: true,
+            selected_test_log_scale: false,
             selected_test_results: (TType::Unknown, Vec::new()),
             selected_test_statistics: TestStats::default(),
 
@@ -483,13 +501,18 @@ impl Default for MyApp {
 }
 
 impl MyApp {
-    fn update_stats(&mut self, ctx: &egui::Context) {
+    // incremental: only the panels touched since the last update are recomputed (auto-update)
+    fn update_stats(&mut self, ctx: &egui::Context, incremental: bool) {
         let mut lock = self.log_master.write().unwrap();
 
-        lock.update();
+        if incremental {
+            lock.update_incremental();
+        } else {
+            lock.update();
+        }
         self.yields = lock.get_yields();
         self.mb_yields = lock.get_mb_yields();
-        self.failures = lock.get_failures(self.fl_setting);
+        self.failures = lock.get_failures_w_suppression(self.fl_setting, SuppressMode::ToBottom);
         self.hourly_stats = lock.get_hourly_mb_stats();
         self.multiboard_results = lock.get_mb_results();
         self.limitchanges = lock.get_tests_w_limit_changes();
@@ -637,20 +660,24 @@ impl eframe::App for MyApp {
 
                     let time_now = Local::now().naive_local();
                     let hours_now = time_now.hour();
-                    if (6..14).contains(&hours_now) {
-                        self.time_start = NaiveTime::from_hms_opt(6, 0, 0).unwrap();
-                        self.time_end = NaiveTime::from_hms_opt(13, 59, 59).unwrap();
-                    } else if (14..22).contains(&hours_now) {
-                        self.time_start = NaiveTime::from_hms_opt(14, 0, 0).unwrap();
-                        self.time_end = NaiveTime::from_hms_opt(21, 59, 59).unwrap();
+                    let [morning, afternoon, night] = self.shift_starts;
+                    let start_of = |h: u32| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+                    let end_before = |h: u32| NaiveTime::from_hms_opt((h + 23) % 24, 59, 59).unwrap();
+
+                    if (morning..afternoon).contains(&hours_now) {
+                        self.time_start = start_of(morning);
+                        self.time_end = end_before(afternoon);
+                    } else if (afternoon..night).contains(&hours_now) {
+                        self.time_start = start_of(afternoon);
+                        self.time_end = end_before(night);
                     } else {
-                        if hours_now < 6 {
+                        if hours_now < morning {
                             self.date_start = self.date_start.pred_opt().unwrap();
                         } else {
                             self.date_end = self.date_end.succ_opt().unwrap();
                         }
-                        self.time_start = NaiveTime::from_hms_opt(22, 0, 0).unwrap();
-                        self.time_end = NaiveTime::from_hms_opt(5, 59, 59).unwrap();
+                        self.time_start = start_of(night);
+                        self.time_end = end_before(morning);
                     }
 
                     self.time_start_string = self.time_start.format("%H:%M:%S").to_string();
@@ -744,7 +771,8 @@ impl eframe::App for MyApp {
 
                 if xx == mm {
                     self.loading = false;
-                    self.update_stats(ctx);
+                    self.log_master.write().unwrap().finalize();
+                    self.update_stats(ctx, false);
                 }
             } else if self.auto_update.enabled {
                 match self.auto_update.state() {
@@ -766,7 +794,7 @@ impl eframe::App for MyApp {
                         );
 
                         if number != 0 {
-                            self.update_stats(ctx);
+                            self.update_stats(ctx, true);
                         }
                     }
                     AUState::Loading => (),
@@ -868,9 +896,13 @@ impl eframe::App for MyApp {
                         .log_master
                         .read()
                         .unwrap()
-                        .get_failures(self.fl_setting);
+                        .get_failures_w_suppression(self.fl_setting, SuppressMode::ToBottom);
                 }
 
+                // Right click on a test toggles its suppression
+                let mut toggle_suppression: Option<(usize, bool)> = None;
+                let default_color = ui.visuals().text_color();
+
                 if !self.failures.is_empty() {
                     TableBuilder::new(ui)
                         .striped(true)
@@ -878,27 +910,50 @@ impl eframe::App for MyApp {
                         .column(Column::remainder())
                         .body(|mut body| {
                             for fail in &self.failures {
+                                let text_color = if fail.suppressed {
+                                    Color32::GRAY
+                                } else {
+                                    default_color
+                                };
+
                                 body.row(16.0, |mut row| {
                                     row.col(|ui| {
-                                        if ui
-                                            .add(
-                                                egui::Label::new(fail.name.to_owned())
-                                                    .truncate(true)
-                                                    .sense(Sense::click()),
+                                        let response = ui.add(
+                                            egui::Label::new(
+                                                RichText::new(fail.name.to_owned())
+                                                    .color(text_color),
                                             )
-                                            .clicked()
-                                        {
+                                            .truncate(true)
+                                            .sense(Sense::click()),
+                                        );
+
+                                        if response.clicked() {
                                             self.selected_test_buf = fail.name.clone();
                                             self.mode = AppMode::Plot;
+                                        } else if response
+                                            .clicked_by(egui::PointerButton::Secondary)
+                                        {
+                                            toggle_suppression =
+                                                Some((fail.test_id, !fail.suppressed));
                                         }
                                     });
                                     row.col(|ui| {
-                                        ui.label(format!("{}", fail.total));
+                                        ui.label(
+                                            RichText::new(format!("{}", fail.total))
+                                                .color(text_color),
+                                        );
                                     });
                                 });
                             }
                         });
                 }
+
+                if let Some((test_id, suppressed)) = toggle_suppression {
+                    let mut lfh = self.log_master.write().unwrap();
+                    lfh.set_suppressed(test_id, suppressed);
+                    self.failures =
+                        lfh.get_failures_w_suppression(self.fl_setting, SuppressMode::ToBottom);
+                }
             });
         });
 
@@ -1079,16 +1134,33 @@ impl eframe::App for MyApp {
                         );
 
                         ui.checkbox(&mut self.selected_test_show_stats, "Statistics");
+
+                        if self.selected_test_show_stats
+                            && ui.checkbox(&mut self.selected_test_stats_gs, "Include GS").changed()
+                        {
+                            self.selected_test_statistics = lfh.get_statistics_for_test_opts(
+                                self.selected_test,
+                                self.selected_test_stats_gs,
+                            );
+                        }
+
+                        if ui.checkbox(&mut self.selected_test_log_scale, "Log scale").changed() {
+                            reset_plot = true;
+                        }
                     });
 
                     ui.separator();
 
-                    if let Some(x) = testlist.iter().position(|p| p.0 == self.selected_test_buf) {
+                    if let Some(x) = lfh.get_test_id(&self.selected_test_buf) {
                         if x != self.selected_test || self.selected_test_results.1.is_empty() {
                             self.selected_test = x;
                             println!("INFO: Loading results for test nbr {}!", self.selected_test);
                             self.selected_test_results = lfh.get_stats_for_test(self.selected_test);
-                            self.selected_test_statistics = lfh.get_statistics_for_test(self.selected_test);
+                            self.selected_test_statistics = lfh.get_statistics_for_test_opts(
+                                self.selected_test,
+                                self.selected_test_stats_gs,
+                            );
+                            self.selected_test_log_scale = self.selected_test_results.0.prefers_log_scale();
 
                             self.selected_test_index = 0;
                             reset_plot = true;
@@ -1103,18 +1175,33 @@ impl eframe::App for MyApp {
                     // Statistics:
                     if self.selected_test_show_stats {
                         ui.vertical_centered(|ui| {
-                            ui.label(format!("Min: {:+1.4E}   Max: {:+1.4E}   Avg: {:+1.4E}   StdDev: {:+1.4E}   Cpk: {}", 
+                            ui.label(format!("n={}   Min: {:+1.4E}   Max: {:+1.4E}   Avg: {:+1.4E}   Median: {:+1.4E}   StdDev: {:+1.4E}   Cpk: {}   Ppk: {}", 
+                                self.selected_test_statistics.count,
                                 self.selected_test_statistics.min,
                                 self.selected_test_statistics.max,
                                 self.selected_test_statistics.avg,
+                                self.selected_test_statistics.median,
                                 self.selected_test_statistics.std_dev,
-                                self.selected_test_statistics.cpk
+                                self.selected_test_statistics.cpk,
+                                self.selected_test_statistics.ppk
                             ));
                         });
                     }
                     
                     // Insert plot here
 
+                    // On log scale the plot shows log10(y), values <= 0 can't be shown
+                    let log_scale = self.selected_test_log_scale;
+                    let scale_y = |y: f64| -> Option<f64> {
+                        if !log_scale {
+                            Some(y)
+                        } else if y > 0.0 {
+                            Some(y.log10())
+                        } else {
+                            None
+                        }
+                    };
+
                     let ppoints: PlotPoints = self
                         .selected_test_results
                         .1
@@ -1129,7 +1216,7 @@ impl eframe::App for MyApp {
                             }
 
                             if r.2.1.is_finite() {
-                                Some([r.0 as f64, r.2 .1 as f64])
+                                scale_y(r.2 .1 as f64).map(|y| [r.0 as f64, y])
                             } else {
                                 None
                             }
@@ -1151,13 +1238,13 @@ impl eframe::App for MyApp {
 
                             if let TLimit::Lim3(_, x, _) = r.3 {
                                 if x.is_finite() {
-                                    Some([r.0 as f64, x as f64])
+                                    scale_y(x as f64).map(|y| [r.0 as f64, y])
                                 } else {
                                     None
                                 }
                             } else if let TLimit::Lim2(x, _) = r.3 {
                                 if x.is_finite() {
-                                    Some([r.0 as f64, x as f64])
+                                    scale_y(x as f64).map(|y| [r.0 as f64, y])
                                 } else {
                                     None
                                 }
@@ -1177,7 +1264,7 @@ impl eframe::App for MyApp {
                             }
 
                             if let TLimit::Lim3(x, _, _) = r.3 {
-                                Some([r.0 as f64, x as f64])
+                                scale_y(x as f64).map(|y| [r.0 as f64, y])
                             } else {
                                 None
                             }
@@ -1194,9 +1281,9 @@ impl eframe::App for MyApp {
                             }
 
                             if let TLimit::Lim3(_, _, x) = r.3 {
-                                Some([r.0 as f64, x as f64])
+                                scale_y(x as f64).map(|y| [r.0 as f64, y])
                             } else if let TLimit::Lim2(_, x) = r.3 {
-                                Some([r.0 as f64, x as f64])
+                                scale_y(x as f64).map(|y| [r.0 as f64, y])
                             } else {
                                 None
                             }
@@ -1214,18 +1301,26 @@ impl eframe::App for MyApp {
 
                     let lower_limit = Line::new(lower_limit_p).color(Color32::RED).name("MIN");
 
-                    let mut plot = Plot::new("Test results")
-                        .custom_x_axes(vec![egui_plot::AxisHints::new_x().formatter(x_formatter)])
-                        .custom_y_axes(vec![egui_plot::AxisHints::new_y()
-                            .formatter(y_formatter)
-                            .label(self.selected_test_results.0.unit())])
-                        .coordinates_formatter(
-                            egui_plot::Corner::RightTop,
+                    let (y_axis, coordinates) = if log_scale {
+                        (
+                            egui_plot::AxisHints::new_y().formatter(y_formatter_log),
+                            egui_plot::CoordinatesFormatter::new(c_formater_log),
+                        )
+                    } else {
+                        (
+                            egui_plot::AxisHints::new_y().formatter(y_formatter),
                             egui_plot::CoordinatesFormatter::new(c_formater),
                         )
-                        .label_formatter(|name, value| {
+                    };
+
+                    let mut plot = Plot::new("Test results")
+                        .custom_x_axes(vec![egui_plot::AxisHints::new_x().formatter(x_formatter)])
+                        .custom_y_axes(vec![y_axis.label(self.selected_test_results.0.unit())])
+                        .coordinates_formatter(egui_plot::Corner::RightTop, coordinates)
+                        .label_formatter(move |name, value| {
+                            let y = if log_scale { 10f64.powf(value.y) } else { value.y };
                             if !name.is_empty() {
-                                format!("{}: {:+1.4E}", name, value.y)
+                                format!("{}: {:+1.4E}", name, y)
                             } else {
                                 "".to_owned()
                             }
@@ -1430,6 +1525,17 @@ impl eframe::App for MyApp {
                     &mut self.export_settings.only_final_logs,
                     MESSAGE_E[EXPORT_FINAL_ONLY][self.lang],
                 );
+                ui.add_enabled(
+                    self.export_settings.only_final_logs,
+                    egui::Checkbox::new(
+                        &mut self.export_settings.final_by_panel,
+                        MESSAGE_E[EXPORT_FINAL_PANEL][self.lang],
+                    ),
+                );
+                ui.checkbox(
+                    &mut self.export_settings.canonical_order,
+                    MESSAGE_E[EXPORT_CANONICAL][self.lang],
+                );
                 ui.horizontal(|ui| {
                     ui.monospace(MESSAGE_E[EXPORT_MODE][self.lang]);
                     ui.selectable_value(
@@ -1442,6 +1548,11 @@ impl eframe::App for MyApp {
                         ExportMode::FailuresOnly,
                         MESSAGE_E[EXPORT_MODE_FTO][self.lang],
                     );
+                    ui.selectable_value(
+                        &mut self.export_settings.mode,
+                        ExportMode::FailuresAndLimitChanges,
+                        MESSAGE_E[EXPORT_MODE_FTO_LIM][self.lang],
+                    );
                     ui.selectable_value(
                         &mut self.export_settings.mode,
                         ExportMode::Manual,
@@ -1460,13 +1571,16 @@ impl eframe::App for MyApp {
                 if ui.button(MESSAGE_E[SAVE][self.lang]).clicked() && !self.loading {
                     if let Some(path) = rfd::FileDialog::new()
                         .add_filter("XLSX", &["xlsx"])
+                        .add_filter("CSV", &["csv"])
                         .set_file_name("out.xlsx")
                         .save_file()
                     {
-                        self.log_master
-                            .read()
-                            .unwrap()
-                            .export(path, &self.export_settings);
+                        let lock = self.log_master.read().unwrap();
+                        if path.extension().is_some_and(|x| x == "csv") {
+                            lock.export_csv(path, &self.export_settings);
+                        } else {
+                            lock.export(path, &self.export_settings);
+                        }
                     }
                 }
 
@@ -1522,6 +1636,15 @@ fn y_formatter(
     format!("{:+1.1E}", tick.value)
 }
 
+// Log scale: the plot contains log10(y)
+fn y_formatter_log(
+    tick: egui_plot::GridMark,
+    _max_digits: usize,
+    _range: &RangeInclusive<f64>,
+) -> String {
+    format!("{:+1.1E}", 10f64.powf(tick.value))
+}
+
 fn x_formatter(
     tick: egui_plot::GridMark,
     _max_digits: usize,
@@ -1538,6 +1661,12 @@ fn c_formater(point: &egui_plot::PlotPoint, _: &egui_plot::PlotBounds) -> String
     format!("x: {:+1.4E}\t t: {}", point.y, t.format("%F %R"))
 }
 
+fn c_formater_log(point: &egui_plot::PlotPoint, _: &egui_plot::PlotBounds) -> String {
+    let t: DateTime<Utc> = DateTime::from_timestamp(point.x as i64, 0).unwrap();
+
+    format!("x: {:+1.4E}\t t: {}", 10f64.powf(point.y), t.format("%F %R"))
+}
+
 fn draw_result_box(ui: &mut egui::Ui, result: &BResult, gs: bool) -> egui::Response {
     let desired_size = egui::vec2(10.0, 10.0);
     let (rect, response) = ui.allocate_exact_size(desired_size, egui::Sense::click());
@@ -2,12 +2,18 @@
 
 #![allow(non_snake_case)]
 
-use std::{fs, io::Write};
+use std::{fs, io, io::Write, path::Path};
 use pwhash::bcrypt;
 
 /// Default filename for the file containing local userdata
 static USER_LIST: &str = "users";
 
+/// Default bcrypt cost used by `User::create_hash`
+pub const DEFAULT_HASH_COST: u32 = bcrypt::DEFAULT_COST;
+
+/// Error returned by `User::create_hash`, so callers don't need a direct pwhash dependency
+pub use pwhash::error::Error as HashError;
+
 /// Authentication levels, based on which users get privileges
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum UserLevel {
@@ -51,11 +57,13 @@ impl UserLevel {
 /// name: name of the user, public
 /// level: authentication level of the user, public
 /// hash: hashed password of the user. Uses salt and pepper. 
+/// enabled: disabled users are kept in the list, but can't log in
 #[derive(Debug, Clone)]
 pub struct User {
     pub name: String,
     pub level: UserLevel,
     hash: String,
+    enabled: bool,
 }
 
 impl User {
@@ -65,12 +73,38 @@ impl User {
             name,
             level,
             hash: String::new(),
+            enabled: true,
         }
     }
 
-    /// Creates hash for the given password
-    pub fn create_hash(&mut self, pass: &str) {
-        self.hash = bcrypt::hash(self.level.pepper(pass)).unwrap();
+    /// Enables or disables the login of the user
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Creates hash for the given password, using DEFAULT_HASH_COST
+    pub fn create_hash(&mut self, pass: &str) -> pwhash::Result<()> {
+        self.create_hash_with_cost(pass, DEFAULT_HASH_COST)
+    }
+
+    /// Creates hash for the given password with the given bcrypt cost.
+    /// Lower cost means faster login on slow machines. Valid range is 4-31.
+    pub fn create_hash_with_cost(&mut self, pass: &str, cost: u32) -> pwhash::Result<()> {
+        if !(bcrypt::MIN_COST..=bcrypt::MAX_COST).contains(&cost) {
+            return Err(pwhash::error::Error::InvalidRounds);
+        }
+
+        let setup = bcrypt::BcryptSetup {
+            cost: Some(cost),
+            ..Default::default()
+        };
+
+        self.hash = bcrypt::hash_with(setup, self.level.pepper(pass))?;
+        Ok(())
     }
 
     /// Checks if the given password matches the stored hash
@@ -79,11 +113,68 @@ impl User {
     }
 }
 
+/// Finds the user by name (case-insensitive) and checks the password.
+/// Returns the level of the user, if the login was successful. Disabled users can't log in.
+pub fn authenticate(users: &[User], name: &str, pass: &str) -> Option<UserLevel> {
+    authenticate_user(users, name, pass).map(|u| u.level)
+}
+
+/// Same as `authenticate`, but returns the logged in user itself.
+/// Login windows should use this (or `authenticate`) instead of checking the users by hand.
+pub fn authenticate_user<'a>(users: &'a [User], name: &str, pass: &str) -> Option<&'a User> {
+    users
+        .iter()
+        .find(|u| u.name.to_lowercase() == name.to_lowercase())
+        .filter(|u| u.enabled && u.check_pw(pass))
+}
+
+/// Escapes a field for the USER_LIST file: '\' and '|' get a '\' prefix,
+/// as does a leading '!', which would otherwise mark the line as a comment.
+fn escape_field(field: &str) -> String {
+    let mut ret = String::with_capacity(field.len());
+
+    for (i, c) in field.chars().enumerate() {
+        if c == '\\' || c == '|' || (i == 0 && c == '!') {
+            ret.push('\\');
+        }
+        ret.push(c);
+    }
+
+    ret
+}
+
+/// Splits a line of the USER_LIST file at the unescaped '|' characters, and removes the escapes.
+fn split_fields(line: &str) -> Vec<String> {
+    let mut ret = Vec::new();
+    let mut field = String::new();
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    field.push(next);
+                }
+            }
+            '|' => ret.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    ret.push(field);
+
+    ret
+}
+
 /// Load the user data from the default USER_LIST file.
 pub fn load_user_list() -> Vec<User> {
+    load_user_list_from(USER_LIST)
+}
+
+/// Load the user data from the given file.
+pub fn load_user_list_from<P: AsRef<Path>>(path: P) -> Vec<User> {
     let mut ret = Vec::new();
 
-    if let Ok(fileb) = fs::read_to_string(USER_LIST) {
+    if let Ok(fileb) = fs::read_to_string(path) {
         let lines: Vec<String> = fileb
             .lines()
             .filter(|f| !f.starts_with('!') && !f.is_empty()) // Lines starting with '!' are considered comments
@@ -91,12 +182,13 @@ pub fn load_user_list() -> Vec<User> {
             .collect();
 
         for line in lines {
-            let tokens: Vec<&str> = line.split('|').collect(); // The fields are seperated with a '|' character
+            let tokens = split_fields(&line); // The fields are seperated with a '|' character
             if tokens.len() >= 3 {
                 ret.push(User {
-                    name: tokens[0].to_string(),
-                    level: tokens[1].into(),
-                    hash: tokens[2].to_string(),
+                    name: tokens[0].clone(),
+                    level: tokens[1].as_str().into(),
+                    hash: tokens[2].clone(),
+                    enabled: tokens.get(3).map(String::as_str) != Some("0"), // Optional 4th field, old files have only 3
                 })
             }
         }
@@ -106,10 +198,79 @@ pub fn load_user_list() -> Vec<User> {
 }
 
 /// Export the given user data to the default USER_LIST file.
-pub fn save_user_list(users: &[User]) {
-    if let Ok(mut file) = fs::File::create(USER_LIST) {
-        for user in users {
-            file.write_all(format!("{}|{}|{}\n", user.name, user.level.print(), user.hash).as_bytes()).unwrap();
+pub fn save_user_list(users: &[User]) -> io::Result<()> {
+    save_user_list_to(USER_LIST, users)
+}
+
+/// Export the given user data to the given file.
+pub fn save_user_list_to<P: AsRef<Path>>(path: P, users: &[User]) -> io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    for user in users {
+        file.write_all(format!(
+            "{}|{}|{}|{}\n",
+            escape_field(&user.name),
+            user.level.print(),
+            user.hash,
+            if user.enabled { "1" } else { "0" }
+        ).as_bytes())?;
+    }
+
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("ICT_auth_{}_{name}", std::process::id()))
+    }
+
+    #[test]
+    fn special_names_round_trip() {
+        let path = temp_file("special_names");
+        let names = ["a|b", "back\\slash", "!not_a_comment", "end\\", "x|\\|y"];
+
+        let mut users = Vec::new();
+        for name in names {
+            let mut user = User::new(name.to_string(), UserLevel::Engineer);
+            user.create_hash_with_cost("password", bcrypt::MIN_COST).unwrap();
+            users.push(user);
+        }
+
+        save_user_list_to(&path, &users).unwrap();
+        let loaded = load_user_list_from(&path);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(loaded.len(), names.len());
+        for (user, name) in loaded.iter().zip(names) {
+            assert_eq!(user.name, name);
+            assert_eq!(user.level, UserLevel::Engineer);
+            assert!(user.check_pw("password"));
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn disabled_user_cannot_log_in() {
+        let path = temp_file("disabled");
+
+        let mut user = User::new("Operator".to_string(), UserLevel::Technician);
+        user.create_hash_with_cost("secret", bcrypt::MIN_COST).unwrap();
+        let mut users = vec![user];
+
+        assert_eq!(authenticate(&users, "operator", "secret"), Some(UserLevel::Technician));
+        assert_eq!(authenticate(&users, "operator", "Secret"), None);
+
+        users[0].set_enabled(false);
+        assert_eq!(authenticate(&users, "Operator", "secret"), None);
+
+        // The flag survives saving, old 3 field lines load as enabled
+        save_user_list_to(&path, &users).unwrap();
+        let loaded = load_user_list_from(&path);
+        fs::write(&path, format!("Old|0|{}\n", users[0].hash)).unwrap();
+        let old = load_user_list_from(&path);
+        let _ = fs::remove_file(&path);
+
+        assert!(!loaded[0].is_enabled());
+        assert!(old[0].is_enabled());
+    }
+}
@@ -33,13 +33,15 @@ struct NewUser {
     level: UserLevel,
 }
 
-impl From<&NewUser> for User {
-    fn from(val: &NewUser) -> Self {
+impl TryFrom<&NewUser> for User {
+    type Error = HashError;
+
+    fn try_from(val: &NewUser) -> Result<Self, Self::Error> {
         let mut ret = User::new(val.name.clone(), val.level);
 
-        ret.create_hash(&val.pass);
+        ret.create_hash(&val.pass)?;
 
-        ret
+        Ok(ret)
     }
 }
 
@@ -68,6 +70,8 @@ struct MyApp {
     login_pass: String,
 
     new_user: NewUser,
+
+    message: String, // Result of the last save / add, shown under the login panel
 }
 
 impl Default for MyApp {
@@ -85,6 +89,8 @@ impl Default for MyApp {
                 pass2: String::new(),
                 level: UserLevel::Technician,
             },
+
+            message: String::new(),
         }
     }
 }
@@ -107,13 +113,13 @@ impl eframe::App for MyApp {
                         ui.add(egui::Button::new("Login").min_size(Vec2 { x: 50.0, y: 15.0 }));
 
                     if resp.clicked() {
-                        for user in self.users.iter() {
-                            if user.name == self.login_name && user.check_pw(&self.login_pass) {
-                                println!("Login as: {}", user.name);
-                                self.current_user = Some(user.clone());
-                                self.login_name.clear();
-                                self.login_pass.clear();
-                            }
+                        if let Some(user) =
+                            authenticate_user(&self.users, &self.login_name, &self.login_pass)
+                        {
+                            println!("Login as: {}", user.name);
+                            self.current_user = Some(user.clone());
+                            self.login_name.clear();
+                            self.login_pass.clear();
                         }
                     }
 
@@ -130,7 +136,11 @@ impl eframe::App for MyApp {
                         ui.add(egui::Button::new("Save").min_size(Vec2 { x: 50.0, y: 15.0 }));
                     if resp.clicked() {
                         println!("Save");
-                        save_user_list(&self.users);
+                        self.message = match save_user_list(&self.users) {
+                            Ok(()) => String::from("Saved"),
+                            Err(e) => format!("ERR: Failed to save the user list: {e}"),
+                        };
+                        println!("{}", self.message);
                     }
 
                     let resp =
@@ -141,6 +151,10 @@ impl eframe::App for MyApp {
                     }
                 });
             });
+
+            if !self.message.is_empty() {
+                ui.label(&self.message);
+            }
         });
 
         if let Some(user) = &self.current_user {
@@ -193,8 +207,17 @@ impl eframe::App for MyApp {
                             ui.add(egui::Button::new("Add").min_size(Vec2 { x: 50.0, y: 15.0 }));
                             if resp.clicked() && self.new_user.all_ok() {
                                 println!("Adding new user");
-                                self.users.push(User::from(&self.new_user));
-                                self.new_user.clear();
+                                match User::try_from(&self.new_user) {
+                                    Ok(user) => {
+                                        self.users.push(user);
+                                        self.new_user.clear();
+                                        self.message.clear();
+                                    }
+                                    Err(e) => {
+                                        self.message = format!("ERR: Failed to create the password hash: {e}");
+                                        println!("{}", self.message);
+                                    }
+                                }
                             }
                         });
                     });
@@ -203,11 +226,16 @@ impl eframe::App for MyApp {
         }
 
         egui::CentralPanel::default().show(ctx, |ui| {
+            // Only admins can disable accounts, and not their own
+            let current_user = self.current_user.clone();
+            let is_admin = current_user.as_ref().is_some_and(|u| u.level == UserLevel::Admin);
+
             TableBuilder::new(ui)
                 .striped(true)
                 .column(Column::initial(40.0).resizable(false)) // ID
                 .column(Column::initial(180.0).resizable(true)) // Name
                 .column(Column::initial(100.0).resizable(false)) // Level
+                .column(Column::initial(50.0).resizable(false)) // Enabled
                 .header(20.0, |mut header| {
                     header.col(|ui| {
                         ui.label("#");
@@ -218,14 +246,16 @@ impl eframe::App for MyApp {
                     header.col(|ui| {
                         ui.label("Szint");
                     });
+                    header.col(|ui| {
+                        ui.label("Aktív");
+                    });
                 })
                 .body(|mut body| {
-                    for (x, result) in self.users.iter().enumerate() {
+                    for (x, result) in self.users.iter_mut().enumerate() {
                         body.row(14.0, |mut row| {
-                            if let Some(x) = &self.current_user {
-                                if x.name == result.name {
-                                    row.set_selected(true);
-                                }
+                            let is_current = current_user.as_ref().is_some_and(|u| u.name == result.name);
+                            if is_current {
+                                row.set_selected(true);
                             }
 
                             row.col(|ui| {
@@ -237,6 +267,17 @@ impl eframe::App for MyApp {
                             row.col(|ui| {
                                 ui.label(format!("{:?}", result.level));
                             });
+                            row.col(|ui| {
+                                let mut enabled = result.is_enabled();
+                                let resp = ui.add_enabled(
+                                    is_admin && !is_current,
+                                    egui::Checkbox::without_text(&mut enabled),
+                                );
+                                if resp.changed() {
+                                    println!("User {} enabled: {enabled}", result.name);
+                                    result.set_enabled(enabled);
+                                }
+                            });
                         });
                     }
                 });
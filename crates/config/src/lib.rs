@@ -9,10 +9,18 @@ use anyhow::bail;
 pub const CONFIG: &str = "config.ini";
 pub const PRODUCT_LIST: &str = "products";
 pub const GOLDEN_LIST: &str = "golden_samples";
+pub const SUPPRESSED_LIST: &str = "suppressed_tests";
+
+const DEFAULT_AUTO_UPDATE_SECS: u64 = 30;
+pub const DEFAULT_SHIFT_STARTS: [u32; 3] = [6, 14, 22]; // morning, afternoon, night
 
 /* Product
 '!' starts a comment
 Product Name | Boards on panel | Log file directory | DMC patterns
+Tokens starting with '#' are modifiers:
+- #inv: the positions are inverted
+- #golden:PATTERN: serials matching the pattern are golden samples, '?' matches any character
+- #rev:A,B: accepted revisions of the product, without it every revision matches
 */
 
 #[derive(Debug, Default, Clone)]
@@ -21,11 +29,22 @@ pub struct Product {
     patterns: Vec<String>,
     boards_on_panel: u8,
     log_dir: PathBuf,
-    modifiers: Vec<String>
+    modifiers: Vec<String>,
+    revisions: Vec<String>
 }
 
 pub fn load_product_list<P: AsRef<Path> + std::fmt::Debug>(path: P, load_all: bool) -> Vec<Product> {
+    load_product_list_with_report(path, load_all).0
+}
+
+// Same as load_product_list, but also returns the products left out because of a missing log directory:
+// (name, log directory). With load_all nothing is left out, but the missing directories are still reported.
+pub fn load_product_list_with_report<P: AsRef<Path> + std::fmt::Debug>(
+    path: P,
+    load_all: bool,
+) -> (Vec<Product>, Vec<(String, PathBuf)>) {
     let mut list = Vec::new();
+    let mut missing = Vec::new();
 
     for line in filter_file(path) {
         let parts: Vec<&str> = line.split('|').map(|f| f.trim()).collect();
@@ -38,27 +57,37 @@ pub fn load_product_list<P: AsRef<Path> + std::fmt::Debug>(path: P, load_all: bo
 
         let mut patterns = Vec::new();
         let mut modifiers = Vec::new();
+        let mut revisions = Vec::new();
 
         for token in parts.iter().skip(3) {
-            if token.starts_with('#') {
+            if let Some(revs) = token.strip_prefix("#rev:") {
+                revisions.extend(revs.split(',').map(|f| f.trim().to_string()).filter(|f| !f.is_empty()));
+            } else if token.starts_with('#') {
                 modifiers.push(token.to_string());
             } else {
                 patterns.push(token.to_string())
             }
         }
 
-        if log_dir.try_exists().is_ok_and(|x| x) || load_all {
+        let dir_exists = log_dir.try_exists().is_ok_and(|x| x);
+        if !dir_exists {
+            log::debug!("Log directory of {} is missing: {:?}", parts[0], log_dir);
+            missing.push((parts[0].to_owned(), log_dir.clone()));
+        }
+
+        if dir_exists || load_all {
             list.push(Product {
                 name: parts[0].to_owned(),
                 patterns,
                 boards_on_panel,
                 log_dir,
-                modifiers
+                modifiers,
+                revisions
             });
         }
     }
 
-    list
+    (list, missing)
 }
 
 pub fn get_product_for_serial<P: AsRef<Path> + std::fmt::Debug>(path: P, serial: &str) -> Option<Product> {
@@ -77,6 +106,17 @@ pub fn get_product_for_serial<P: AsRef<Path> + std::fmt::Debug>(path: P, serial:
     None
 }
 
+// Minimum length for validate_serial. check_serial keeps the stricter 20 characters for both layouts.
+// DCDC: !YYDDDxxxx! + the 9 character version ID.
+// Standard: VLLDDDxxxxxxx + the product ID, which has no fixed length, at least one character of it is needed.
+fn min_serial_length(serial: &str) -> usize {
+    if serial.starts_with('!') {
+        20
+    } else {
+        14
+    }
+}
+
 impl Product {
     pub fn unknown() -> Self {
         Self { 
@@ -90,12 +130,17 @@ impl Product {
             return false;
         }
 
+        self.matches_pattern(serial)
+    }
+
+    // The product ID part of the serial starts with one of the patterns, without any length check
+    fn matches_pattern(&self, serial: &str) -> bool {
         // Support for DCDC DMCs
         // Format: !YYDDDxxxx!********* (last 9 chars are version ID)
         // version ID starts at char #11
         if serial.starts_with('!') {
             for pattern in &self.patterns {
-                if serial[11..].starts_with(pattern) {
+                if serial.get(11..).is_some_and(|s| s.starts_with(pattern)) {
                     return true;
                 }
             }
@@ -105,7 +150,7 @@ impl Product {
 
         // VLLDDDxxxxxxx*
         for pattern in &self.patterns {
-            if serial[13..].starts_with(pattern) {
+            if serial.get(13..).is_some_and(|s| s.starts_with(pattern)) {
                 return true;
             }
         }
@@ -113,6 +158,67 @@ impl Product {
         false
     }
 
+    // Checks the format of the serial, and the product ID check_serial uses.
+    // DCDC: !YYDDDxxxx!********* - standard: VLLDDDxxxxxxx*
+    // Standard serials with a short product ID pass here, but check_serial still needs 20 characters.
+    pub fn validate_serial(&self, serial: &str) -> Result<(), String> {
+        let min_length = min_serial_length(serial);
+        if serial.len() < min_length {
+            return Err(format!("Serial is too short: {} < {min_length} characters", serial.len()));
+        }
+
+        let dcdc = serial.starts_with('!');
+
+        // Only ASCII is allowed, so the byte indexes below are safe
+        if let Some((i, c)) = serial.char_indices().find(|(i, c)| {
+            !(c.is_ascii_alphanumeric() || (*c == '!' && (*i == 0 || (dcdc && *i == 10))))
+        }) {
+            return Err(format!("Invalid character '{c}' at position {i}"));
+        }
+
+        if dcdc {
+            if serial.as_bytes()[10] != b'!' {
+                return Err("DCDC serial has no '!' at position 10".to_string());
+            }
+
+            if !serial[6..10].chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("DCDC serial number is not numeric: {}", &serial[6..10]));
+            }
+        } else if !serial[6..13].chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("Serial number is not numeric: {}", &serial[6..13]));
+        }
+
+        if !self.matches_pattern(serial) {
+            let id = if dcdc { &serial[11..] } else { &serial[13..] };
+            return Err(format!("Product ID {id} doesn't match any pattern of {}", self.name));
+        }
+
+        Ok(())
+    }
+
+    // Products without a #rev modifier accept every revision
+    pub fn check_revision(&self, rev: &str) -> bool {
+        self.revisions.is_empty() || self.revisions.iter().any(|r| r == rev.trim())
+    }
+
+    pub fn get_revisions(&self) -> &[String] {
+        &self.revisions
+    }
+
+    // Checks the serial against the #golden:PATTERN modifiers
+    pub fn is_golden_serial(&self, serial: &str) -> bool {
+        self.modifiers
+            .iter()
+            .filter_map(|m| m.strip_prefix("#golden:"))
+            .any(|pattern| {
+                serial.len() >= pattern.len()
+                    && pattern
+                        .chars()
+                        .zip(serial.chars())
+                        .all(|(p, c)| p == '?' || p == c)
+            })
+    }
+
     pub fn get_name(&self) -> &str {
         &self.name
     }
@@ -125,12 +231,28 @@ impl Product {
         &self.log_dir
     }
 
+    // #inv: the board numbers of the fixture are in reverse order
+    pub fn is_inverted(&self) -> bool {
+        self.modifiers.iter().any(|f| f == "#inv")
+    }
+
+    // Position of the board on the panel (1..=boards_on_panel) from the board number of the tester,
+    // consistent with get_pos_from_logname (which is 0 based).
+    pub fn logical_position(&self, board_number: usize) -> usize {
+        let bop = self.boards_on_panel as usize;
+        if self.is_inverted() && (1..=bop).contains(&board_number) {
+            bop + 1 - board_number
+        } else {
+            board_number
+        }
+    }
+
     pub fn get_pos_from_logname(&self, log_file_name: &str) -> Option<u8> {
         let filename = log_file_name.split(&['/', '\\']).last()?;
         let pos = filename.split_once('-')?;
 
         if let Ok(p) = pos.0.parse::<u8>() {
-            if self.modifiers.iter().any(|f| f == "#inv") {
+            if self.is_inverted() {
                 Some(self.boards_on_panel - p)
             } else {
                 Some(p-1)
@@ -154,6 +276,8 @@ pub struct Config {
     MES_server: String,
     station_name: String,
     other_stations: Vec<String>,
+    auto_update_secs: u64,
+    shift_starts: [u32; 3], // Starting hours of the morning, afternoon and night shift
 
     AOI_dir: String,
     AOI_line: String,
@@ -163,7 +287,11 @@ pub struct Config {
 impl Config {
     pub fn read<P: AsRef<Path>>(path: P) -> anyhow::Result<Config> {
         let path = path.as_ref();
-        let mut c = Config::default();
+        let mut c = Config {
+            auto_update_secs: DEFAULT_AUTO_UPDATE_SECS,
+            shift_starts: DEFAULT_SHIFT_STARTS,
+            ..Default::default()
+        };
 
         if let Ok(config) = ini::Ini::load_from_file(path) {
             if let Some(jvserver) = config.section(Some("JVSERVER")) {
@@ -210,6 +338,27 @@ impl Config {
                 for station in app.get_all("OTHER_STATIONS") {
                     c.other_stations.push(station.to_string());
                 }
+
+                if let Some(secs) = app.get("AUTO_UPDATE_SECS") {
+                    c.auto_update_secs = secs.parse().unwrap_or(DEFAULT_AUTO_UPDATE_SECS);
+                }
+            }
+
+            if let Some(shifts) = config.section(Some("SHIFTS")) {
+                for (i, key) in ["MORNING_START", "AFTERNOON_START", "NIGHT_START"].iter().enumerate() {
+                    if let Some(hour) = shifts.get(*key) {
+                        match hour.parse::<u32>() {
+                            Ok(h) if h < 24 => c.shift_starts[i] = h,
+                            _ => log::warn!("Invalid hour for {key}: {hour}, using the default"),
+                        }
+                    }
+                }
+
+                // The night shift is the one crossing midnight, so the starts have to be in order
+                if !(c.shift_starts[0] < c.shift_starts[1] && c.shift_starts[1] < c.shift_starts[2]) {
+                    log::warn!("Shift starts are not in order: {:?}, using the defaults", c.shift_starts);
+                    c.shift_starts = DEFAULT_SHIFT_STARTS;
+                }
             }
 
             if let Some(app) = config.section(Some("AOI")) {
@@ -267,6 +416,15 @@ impl Config {
         &self.other_stations
     }
 
+    pub fn get_auto_update_secs(&self) -> u64 {
+        self.auto_update_secs
+    }
+
+    // [morning, afternoon, night] starting hours
+    pub fn get_shift_starts(&self) -> [u32; 3] {
+        self.shift_starts
+    }
+
     pub fn get_AOI_dir(&self) -> &str {
         &self.AOI_dir
     }
@@ -331,6 +489,67 @@ pub fn load_gs_list_for_product<P: AsRef<Path> + std::fmt::Debug>(path: P, produ
     ret
 }
 
+/* Suppressed tests
+'!' starts a comment
+One test name per line. These are known-bad tests, which the GUI should render differently.
+*/
+
+pub fn load_suppressed_list<P: AsRef<Path> + std::fmt::Debug>(path: P) -> Vec<String> {
+    filter_file(path)
+}
+
+pub fn export_suppressed_list(list: &[String]) -> anyhow::Result<()> {
+    let mut file = match fs::File::create(SUPPRESSED_LIST) {
+        Err(e) => {
+            bail!("{e}");
+        }
+        Ok(file) => file
+    };
+
+    for line in list {
+        writeln!(file, "{}", line)?;
+    }
+
+    Ok(())
+}
+
+// Splits the serial to (prefix, serial number, suffix)
+// DCDC: !YYDDDxxxx!********* -> 4 digit serial number
+// Standard: VLLDDDxxxxxxx* -> 7 digit serial number
+fn split_serial(serial: &str) -> Option<(&str, u32, &str)> {
+    let range = if serial.starts_with('!') { 6..10 } else { 6..13 };
+    let number = serial.get(range.clone())?.parse::<u32>().ok()?;
+
+    Some((&serial[..range.start], number, &serial[range.end..]))
+}
+
+// Serial of the first board of the panel, from the serial of the board at `position` (1-based).
+// The boards of a panel have consecutive serials, so every board of the same panel gives the same result.
+// None if the serial has an unknown layout, or the position points before serial 0.
+pub fn first_serial_of_panel(serial: &str, position: usize) -> Option<String> {
+    let (prefix, number, suffix) = split_serial(serial)?;
+    let offset = u32::try_from(position.checked_sub(1)?).ok()?;
+    let first = number.checked_sub(offset)?;
+    let width = if serial.starts_with('!') { 4 } else { 7 };
+
+    Some(format!("{prefix}{first:0width$}{suffix}"))
+}
+
+// True if the two serials are on the same panel of `bop` boards.
+// The position digits are stripped by dividing the serial number by bop, so the panels have to start at
+// serial 1, 1 + bop, 1 + 2*bop, ... Serials with an unknown layout are only equal to themselves.
+pub fn same_panel(a: &str, b: &str, bop: u8) -> bool {
+    match (split_serial(a), split_serial(b)) {
+        (Some((prefix_a, number_a, suffix_a)), Some((prefix_b, number_b, suffix_b))) => {
+            let bop = u32::from(bop.max(1));
+            prefix_a == prefix_b
+                && suffix_a == suffix_b
+                && number_a.checked_sub(1).map(|n| n / bop) == number_b.checked_sub(1).map(|n| n / bop)
+        }
+        _ => a == b,
+    }
+}
+
 pub fn increment_sn(start: &str, boards: u8) -> Vec<String> {
     log::debug!("increment_sn: {start} number_of_boards: {boards}");
     let mut ret = Vec::with_capacity(boards as usize);
@@ -343,7 +562,8 @@ pub fn increment_sn(start: &str, boards: u8) -> Vec<String> {
     // Format: !YYDDDxxxx!********* (last 9 chars are version ID)
     // it only uses 4 digits, not 7! Start pos is the same.
     if start.starts_with('!') {
-        if let Ok(sn) = &start[6..10].parse::<u32>() {
+        // get() instead of indexing: a multibyte character in the range would panic
+        if let Some(Ok(sn)) = start.get(6..10).map(|s| s.parse::<u32>()) {
             for i in 1..boards {
                 let nsn = sn + i as u32;
                 let mut next_sn = start.to_string();
@@ -359,7 +579,7 @@ pub fn increment_sn(start: &str, boards: u8) -> Vec<String> {
 
     // VLLDDDxxxxxxx*
     // x is 7 digits -> u32
-    if let Ok(sn) = &start[6..13].parse::<u32>() {
+    if let Some(Ok(sn)) = start.get(6..13).map(|s| s.parse::<u32>()) {
         for i in 1..boards {
             let nsn = sn + i as u32;
             let mut next_sn = start.to_string();
@@ -381,7 +601,8 @@ pub fn generate_serials(serial: &str, position: u8, max_pos: u8) -> Vec<String>
     // Format: !YYDDDxxxx!********* (last 9 chars are version ID)
     // it only uses 4 digits, not 7! Start pos is the same.
     if serial.starts_with('!') {
-        if let Ok(start) = serial[6..10].parse::<u32>() {
+        // get() instead of indexing: a multibyte character in the range would panic
+        if let Some(Ok(start)) = serial.get(6..10).map(|s| s.parse::<u32>()) {
             let sn = start - position as u32;
             for i in sn..sn + max_pos as u32 {
                 let mut s = serial.to_string();
@@ -398,7 +619,7 @@ pub fn generate_serials(serial: &str, position: u8, max_pos: u8) -> Vec<String>
 
     // VLLDDDxxxxxxx*
     // x is 7 digits -> u32
-    if let Ok(start) = serial[6..13].parse::<u32>() {
+    if let Some(Ok(start)) = serial.get(6..13).map(|s| s.parse::<u32>()) {
         let sn = start - position as u32;
         for i in sn..sn + max_pos as u32 {
             let mut s = serial.to_string();
@@ -418,4 +639,162 @@ pub fn generate_serials(serial: &str, position: u8, max_pos: u8) -> Vec<String>
 
 pub fn query(serial: String) -> std::result::Result<std::process::Child, std::io::Error> {
     std::process::Command::new("query.exe").arg(serial).spawn()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // VLLDDD + 7 digit serial number + product id
+    const STD_1003: &str = "V0L2400001003PRODUCT01";
+    // !YYDDD + 4 digit serial number + ! + 9 char version id
+    const DCDC_1003: &str = "!241231003!ABCDEFGHI";
+
+    #[test]
+    fn first_serial_same_panel() {
+        // bop = 4, first board is 1003
+        for pos in 1..=4 {
+            let serial = format!("V0L240{:07}PRODUCT01", 1002 + pos);
+            assert_eq!(first_serial_of_panel(&serial, pos).as_deref(), Some(STD_1003));
+
+            let serial = format!("!24123{:04}!ABCDEFGHI", 1002 + pos);
+            assert_eq!(first_serial_of_panel(&serial, pos).as_deref(), Some(DCDC_1003));
+        }
+    }
+
+    #[test]
+    fn first_serial_adjacent_panel() {
+        // A panel starting at 1005 is not the same as the one starting at 1003, even if bop = 4
+        let a = first_serial_of_panel(STD_1003, 1);
+        let b = first_serial_of_panel("V0L2400001005PRODUCT01", 1);
+        assert_ne!(a, b);
+
+        // Last board of the 1003 panel vs first board of the next one (edge of bop = 4)
+        let last = first_serial_of_panel("V0L2400001006PRODUCT01", 4);
+        let next = first_serial_of_panel("V0L2400001007PRODUCT01", 1);
+        assert_eq!(last.as_deref(), Some(STD_1003));
+        assert_eq!(next.as_deref(), Some("V0L2400001007PRODUCT01"));
+    }
+
+    #[test]
+    fn first_serial_invalid() {
+        assert_eq!(first_serial_of_panel("NoDMC", 1), None);
+        assert_eq!(first_serial_of_panel(STD_1003, 0), None);
+        // Position before serial 0
+        assert_eq!(first_serial_of_panel("V0L2400000001PRODUCT01", 3), None);
+        // Multibyte character in the serial number field
+        assert_eq!(first_serial_of_panel("V0L240000á001PRODUCT01", 1), None);
+    }
+
+    #[test]
+    fn same_panel_layouts() {
+        // bop = 4, the panel holds 1001..=1004
+        for n in 1001..=1004 {
+            let serial = format!("V0L240{n:07}PRODUCT01");
+            assert!(same_panel("V0L2400001001PRODUCT01", &serial, 4));
+
+            let serial = format!("!24123{n:04}!ABCDEFGHI");
+            assert!(same_panel("!241231001!ABCDEFGHI", &serial, 4));
+        }
+
+        // Edge of the panel
+        assert!(!same_panel("V0L2400001004PRODUCT01", "V0L2400001005PRODUCT01", 4));
+        assert!(!same_panel("!241231004!ABCDEFGHI", "!241231005!ABCDEFGHI", 4));
+
+        // Same serial number, but a different day or product
+        assert!(!same_panel(STD_1003, "V0L2410001003PRODUCT01", 4));
+        assert!(!same_panel(STD_1003, "V0L2400001003PRODUCT02", 4));
+        assert!(!same_panel(DCDC_1003, "!241241003!ABCDEFGHI", 4));
+        assert!(!same_panel(DCDC_1003, "!241231003!ABCDEFGHJ", 4));
+
+        // One board per panel: only the exact serial number matches
+        assert!(same_panel(STD_1003, STD_1003, 1));
+        assert!(!same_panel("V0L2400001003PRODUCT01", "V0L2400001004PRODUCT01", 1));
+    }
+
+    #[test]
+    fn same_panel_invalid() {
+        assert!(same_panel("NoDMC", "NoDMC", 4));
+        assert!(!same_panel("NoDMC", STD_1003, 4));
+        assert!(!same_panel(STD_1003, DCDC_1003, 4));
+        // bop = 0 is treated as one board per panel
+        assert!(same_panel(STD_1003, STD_1003, 0));
+    }
+
+    fn test_product() -> Product {
+        Product {
+            name: "Test product".to_string(),
+            patterns: vec!["PRODUCT01".to_string(), "P2".to_string(), "ABCDEFGHI".to_string()],
+            boards_on_panel: 4,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn validate_serial_valid() {
+        let product = test_product();
+
+        assert_eq!(product.validate_serial(STD_1003), Ok(()));
+        assert_eq!(product.validate_serial(DCDC_1003), Ok(()));
+        // Standard serials with a short product ID
+        assert_eq!(product.validate_serial("V0L2400001003P2"), Ok(()));
+        assert!(!product.check_serial("V0L2400001003P2"));
+    }
+
+    #[test]
+    fn validate_serial_invalid() {
+        let product = test_product();
+
+        let invalid = [
+            "",
+            "V0L2400001003",          // No product ID
+            "!241231003!ABCDE",       // Version ID too short
+            "V0L24000-1003PRODUCT01", // Invalid character
+            "V0L2400001003PRODUCT!1", // '!' is only allowed in DCDC serials
+            "V0L240000A003PRODUCT01", // Serial number is not numeric
+            "!24123A003!ABCDEFGHI",   // DCDC serial number is not numeric
+            "!241231003XABCDEFGHI",   // No '!' at position 10
+            "V0L2400001003OTHER0001", // Unknown product ID
+        ];
+
+        for serial in invalid {
+            assert!(product.validate_serial(serial).is_err(), "{serial}");
+        }
+    }
+
+    #[test]
+    fn accented_serials_do_not_panic() {
+        let product = test_product();
+
+        // 'é' is 2 bytes, these put it on the slicing boundaries (6, 10, 11, 13)
+        let serials = [
+            "V0L24é0001003PRODUCT01",
+            "V0L240000100éPRODUCT01",
+            "V0L2400001003éPRODUCT01",
+            "!24123100é!ABCDEFGHI",
+            "!241231003éABCDEFGHI",
+        ];
+
+        for serial in serials {
+            assert!(!product.check_serial(serial), "{serial}");
+            assert!(product.validate_serial(serial).is_err(), "{serial}");
+        }
+
+        // In the serial number field
+        for serial in [serials[0], serials[1], serials[3]] {
+            assert_eq!(first_serial_of_panel(serial, 2), None, "{serial}");
+            assert_eq!(increment_sn(serial, 4), vec![serial.to_string()], "{serial}");
+            assert_eq!(generate_serials(serial, 1, 4), vec![serial.to_string()], "{serial}");
+        }
+
+        // After the serial number, only the product ID is affected
+        assert_eq!(
+            increment_sn(serials[2], 2),
+            vec![serials[2].to_string(), "V0L2400001004éPRODUCT01".to_string()]
+        );
+        assert_eq!(
+            first_serial_of_panel(serials[4], 2).as_deref(),
+            Some("!241231002éABCDEFGHI")
+        );
+    }
+}
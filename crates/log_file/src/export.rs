@@ -0,0 +1,595 @@
+/*
+Output formats for LogFileHandler::export_to_sink
+
+The traversal of the logs is done once in the handler, the sinks only decide where each piece goes:
+- set_result_strings: optional, called before header, the strings used for Pass/Fail/Unknown
+- header: called once, before anything else
+- test_row: called for each exported test, in export order
+- board_column: called for each exported log, in export order.
+  `marginal` marks the passing results close to a limit, if ExportSettings.marginal_fraction is set.
+- save: writes the result to the given path
+*/
+
+use std::{io, path::Path};
+
+use serde::Serialize;
+
+use crate::{u64_to_string, BResult, TResult, TType, TestStats};
+
+pub trait ExportSink {
+    fn set_result_strings(&mut self, _strings: &[String; 3]) {}
+    fn header(&mut self, product_id: &str);
+    fn test_row(&mut self, index: usize, name: &str, ttype: TType, stats: &TestStats);
+    fn board_column(
+        &mut self,
+        DMC: &str,
+        time: u64,
+        result: BResult,
+        results: &[Option<TResult>],
+        marginal: &[bool],
+    );
+    fn save(&mut self, path: &Path) -> io::Result<()>;
+}
+
+fn default_result_strings() -> [String; 3] {
+    [
+        BResult::Pass.print(),
+        BResult::Fail.print(),
+        BResult::Unknown.print(),
+    ]
+}
+
+fn result_string(strings: &[String; 3], result: BResult) -> String {
+    match result {
+        BResult::Pass => strings[0].clone(),
+        BResult::Fail => strings[1].clone(),
+        BResult::Unknown | BResult::Skipped | BResult::Aborted => strings[2].clone(),
+    }
+}
+
+/* XLSX */
+
+pub struct XlsxSink {
+    book: rust_xlsxwriter::Workbook,
+    vertical: bool,
+    next: u32, // Next free line (vertical) or column (horizontal) for the logs
+    result_strings: [String; 3],
+
+    sci_format: rust_xlsxwriter::Format,
+    marginal_format: rust_xlsxwriter::Format,
+    center_format: rust_xlsxwriter::Format,
+    wrap_format: rust_xlsxwriter::Format,
+}
+
+impl XlsxSink {
+    pub fn new(vertical: bool) -> Self {
+        let mut book = rust_xlsxwriter::Workbook::new();
+        book.add_worksheet();
+
+        Self {
+            book,
+            vertical,
+            next: 8,
+            result_strings: default_result_strings(),
+            sci_format: rust_xlsxwriter::Format::new()
+                .set_align(rust_xlsxwriter::FormatAlign::Center)
+                .set_num_format("0.00E+00"),
+            marginal_format: rust_xlsxwriter::Format::new()
+                .set_align(rust_xlsxwriter::FormatAlign::Center)
+                .set_num_format("0.00E+00")
+                .set_background_color(rust_xlsxwriter::Color::Yellow),
+            center_format: rust_xlsxwriter::Format::new()
+                .set_align(rust_xlsxwriter::FormatAlign::Center)
+                .set_num_format("0.00")
+                .set_text_wrap(),
+            wrap_format: rust_xlsxwriter::Format::new().set_text_wrap(),
+        }
+    }
+}
+
+impl ExportSink for XlsxSink {
+    fn set_result_strings(&mut self, strings: &[String; 3]) {
+        self.result_strings = strings.clone();
+    }
+
+    fn header(&mut self, product_id: &str) {
+        let vertical = self.vertical;
+        let center_format = self.center_format.clone();
+        let sheet = match self.book.worksheet_from_index(0) {
+            Ok(sheet) => sheet,
+            Err(_) => return,
+        };
+
+        if vertical {
+            let _ = sheet.write(0, 0, product_id);
+            let _ = sheet.write(7, 0, "DMC");
+            let _ = sheet.set_column_width(0, 32);
+            let _ = sheet.write(7, 1, "Test time");
+            let _ = sheet.set_column_width(1, 18);
+
+            let _ = sheet.write(0, 2, "Test name:");
+            let _ = sheet.write(1, 2, "Test type:");
+            let _ = sheet.write(2, 2, "Nominal:");
+            let _ = sheet.write(3, 2, "Lower limit:");
+            let _ = sheet.write(4, 2, "Upper limit:");
+            let _ = sheet.write(5, 2, "Std Dev:");
+            let _ = sheet.write(6, 2, "Cpk:");
+            let _ = sheet.write(7, 2, "Log result");
+            let _ = sheet.set_column_width(2, 10);
+        } else {
+            let _ = sheet.write(0, 0, product_id);
+            let _ = sheet.write(2, 0, "Test name");
+            let _ = sheet.set_column_width(0, 22);
+
+            let _ = sheet.write(2, 1, "Test type");
+            let _ = sheet.set_column_width(1, 16);
+
+            let _ = sheet.merge_range(1, 2, 1, 4, "Test limits", &center_format);
+
+            let _ = sheet.write_with_format(2, 2, "Nominal", &center_format);
+            let _ = sheet.set_column_width(2, 10);
+            let _ = sheet.write_with_format(2, 3, "Lower limit", &center_format);
+            let _ = sheet.set_column_width(3, 10);
+            let _ = sheet.write_with_format(2, 4, "Upper limit", &center_format);
+            let _ = sheet.set_column_width(4, 10);
+            let _ = sheet.write_with_format(2, 5, "Average", &center_format);
+            let _ = sheet.set_column_width(5, 10);
+            let _ = sheet.write_with_format(2, 6, "Std Dev", &center_format);
+            let _ = sheet.set_column_width(6, 10);
+            let _ = sheet.write_with_format(2, 7, "Cpk", &center_format);
+            let _ = sheet.set_column_width(7, 10);
+        }
+    }
+
+    fn test_row(&mut self, index: usize, name: &str, ttype: TType, stats: &TestStats) {
+        let vertical = self.vertical;
+        let sci_format = self.sci_format.clone();
+        let center_format = self.center_format.clone();
+        let sheet = match self.book.worksheet_from_index(0) {
+            Ok(sheet) => sheet,
+            Err(_) => return,
+        };
+
+        if vertical {
+            let c: u16 = (index * 2 + 3).try_into().unwrap();
+
+            // Testname and type
+            let _ = sheet.merge_range(0, c, 0, c + 1, name, &center_format);
+            let _ = sheet.merge_range(1, c, 1, c + 1, &ttype.print(), &center_format);
+
+            // Merge for the next 5 rows.
+            for row in 2..7 {
+                let _ = sheet.merge_range(row, c, row, c + 1, "", &center_format);
+            }
+
+            // Nominal, Limits, StdDev, Cpk
+            if let Some(nom) = stats.limits.nominal() {
+                let _ = sheet.write_number_with_format(2, c, nom, &sci_format);
+            }
+
+            if let Some((ll, ul)) = stats.limits.bounds() {
+                let _ = sheet.write_number_with_format(3, c, ll, &sci_format);
+
+                // UL can be +INF
+                if ul.is_finite() {
+                    let _ = sheet.write_number_with_format(4, c, ul, &sci_format);
+                }
+
+                let _ = sheet.write_number_with_format(5, c, stats.std_dev, &sci_format);
+                let _ = sheet.write_number_with_format(6, c, stats.cpk, &center_format);
+            }
+
+            let _ = sheet.write_with_format(7, c, "Result", &center_format);
+            let _ = sheet.write_with_format(7, c + 1, "Value", &center_format);
+
+            let _ = sheet.set_column_width(c, 6);
+            let _ = sheet.set_column_width(c + 1, 10);
+        } else {
+            let l: u32 = (index + 3).try_into().unwrap();
+            let _ = sheet.write(l, 0, name);
+            let _ = sheet.write(l, 1, ttype.print());
+
+            // Nominal, Limits, StdDev, Cpk
+            if let Some(nom) = stats.limits.nominal() {
+                let _ = sheet.write_number_with_format(l, 2, nom, &sci_format);
+            }
+
+            if let Some((ll, ul)) = stats.limits.bounds() {
+                let _ = sheet.write_number_with_format(l, 3, ll, &sci_format);
+
+                // UL can be +INF
+                if ul.is_finite() {
+                    let _ = sheet.write_number_with_format(l, 4, ul, &sci_format);
+                }
+
+                let _ = sheet.write_number_with_format(l, 5, stats.avg, &sci_format);
+                let _ = sheet.write_number_with_format(l, 6, stats.std_dev, &sci_format);
+                let _ = sheet.write_number_with_format(l, 7, stats.cpk, &center_format);
+            }
+        }
+    }
+
+    fn board_column(
+        &mut self,
+        DMC: &str,
+        time: u64,
+        result: BResult,
+        results: &[Option<TResult>],
+        marginal: &[bool],
+    ) {
+        let vertical = self.vertical;
+        let next = self.next;
+        let sci_format = self.sci_format.clone();
+        let marginal_format = self.marginal_format.clone();
+        let wrap_format = self.wrap_format.clone();
+        let result_strings = self.result_strings.clone();
+        let sheet = match self.book.worksheet_from_index(0) {
+            Ok(sheet) => sheet,
+            Err(_) => return,
+        };
+
+        if vertical {
+            let l = next;
+
+            // DMC
+            let _ = sheet.write(l, 0, DMC);
+
+            // Log result and time of test
+            let _ = sheet.write(l, 2, result_string(&result_strings, result));
+            let _ = sheet.write(l, 1, u64_to_string(time));
+
+            // Print measurement results
+            for (i, res) in results.iter().enumerate() {
+                if let Some(res) = res {
+                    let c = i as u16 * 2 + 3;
+                    let format = if marginal.get(i).is_some_and(|m| *m) {
+                        &marginal_format
+                    } else {
+                        &sci_format
+                    };
+
+                    let _ = sheet.write(l, c, result_string(&result_strings, res.0));
+                    let _ = sheet.write_number_with_format(l, c + 1, res.1, format);
+                }
+            }
+
+            self.next += 1;
+        } else {
+            let c: u16 = next.try_into().unwrap();
+
+            // DMC in a merged 2x2 range
+            let _ = sheet.merge_range(0, c, 1, c + 1, DMC, &wrap_format);
+
+            // Log result and time of test
+            let _ = sheet.write(2, c, result_string(&result_strings, result));
+            let _ = sheet.write_with_format(2, c + 1, u64_to_string(time), &wrap_format);
+
+            let _ = sheet.set_column_width(c, 8);
+            let _ = sheet.set_column_width(c + 1, 14);
+
+            // Print measurement results
+            for (i, res) in results.iter().enumerate() {
+                if let Some(res) = res {
+                    let format = if marginal.get(i).is_some_and(|m| *m) {
+                        &marginal_format
+                    } else {
+                        &sci_format
+                    };
+
+                    let _ = sheet.write(3 + i as u32, c, result_string(&result_strings, res.0));
+                    let _ = sheet.write_number_with_format(3 + i as u32, c + 1, res.1, format);
+                }
+            }
+
+            self.next += 2;
+        }
+    }
+
+    fn save(&mut self, path: &Path) -> io::Result<()> {
+        self.book
+            .save(path)
+            .map_err(|e| io::Error::other(e.to_string()))
+    }
+}
+
+/* CSV */
+
+// Uses the same layout as the XLSX export, without the merged cells.
+// The product and the limit/statistics rows of the vertical layout are written as comment lines, starting with '#'.
+pub struct CsvSink {
+    product_id: String,
+    cells: Vec<Vec<String>>,
+    vertical: bool,
+    next: usize,
+    result_strings: [String; 3],
+}
+
+impl CsvSink {
+    pub fn new(vertical: bool) -> Self {
+        Self {
+            product_id: String::new(),
+            cells: Vec::new(),
+            vertical,
+            next: 8,
+            result_strings: default_result_strings(),
+        }
+    }
+
+    fn set(&mut self, row: usize, col: usize, value: String) {
+        while self.cells.len() <= row {
+            self.cells.push(Vec::new());
+        }
+
+        let line = &mut self.cells[row];
+        while line.len() <= col {
+            line.push(String::new());
+        }
+
+        line[col] = value;
+    }
+}
+
+// Fields containing the separator, quotes or line breaks are quoted
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl ExportSink for CsvSink {
+    fn set_result_strings(&mut self, strings: &[String; 3]) {
+        self.result_strings = strings.clone();
+    }
+
+    fn header(&mut self, product_id: &str) {
+        self.product_id = product_id.to_string();
+
+        if self.vertical {
+            self.set(7, 0, "DMC".to_string());
+            self.set(7, 1, "Test time".to_string());
+
+            self.set(0, 2, "Test name:".to_string());
+            self.set(1, 2, "Test type:".to_string());
+            self.set(2, 2, "Nominal:".to_string());
+            self.set(3, 2, "Lower limit:".to_string());
+            self.set(4, 2, "Upper limit:".to_string());
+            self.set(5, 2, "Std Dev:".to_string());
+            self.set(6, 2, "Cpk:".to_string());
+            self.set(7, 2, "Log result".to_string());
+        } else {
+            self.set(2, 0, "Test name".to_string());
+            self.set(2, 1, "Test type".to_string());
+            self.set(2, 2, "Nominal".to_string());
+            self.set(2, 3, "Lower limit".to_string());
+            self.set(2, 4, "Upper limit".to_string());
+            self.set(2, 5, "Average".to_string());
+            self.set(2, 6, "Std Dev".to_string());
+            self.set(2, 7, "Cpk".to_string());
+        }
+    }
+
+    fn test_row(&mut self, index: usize, name: &str, ttype: TType, stats: &TestStats) {
+        if self.vertical {
+            let c = index * 2 + 3;
+
+            self.set(0, c, name.to_string());
+            self.set(1, c, ttype.print());
+
+            if let Some(nom) = stats.limits.nominal() {
+                self.set(2, c, format!("{:E}", nom));
+            }
+
+            if let Some((ll, ul)) = stats.limits.bounds() {
+                self.set(3, c, format!("{:E}", ll));
+
+                // UL can be +INF
+                if ul.is_finite() {
+                    self.set(4, c, format!("{:E}", ul));
+                }
+
+                self.set(5, c, format!("{:E}", stats.std_dev));
+                self.set(6, c, format!("{:.2}", stats.cpk));
+            }
+
+            self.set(7, c, "Result".to_string());
+            self.set(7, c + 1, "Value".to_string());
+        } else {
+            let l = index + 3;
+
+            self.set(l, 0, name.to_string());
+            self.set(l, 1, ttype.print());
+
+            if let Some(nom) = stats.limits.nominal() {
+                self.set(l, 2, format!("{:E}", nom));
+            }
+
+            if let Some((ll, ul)) = stats.limits.bounds() {
+                self.set(l, 3, format!("{:E}", ll));
+
+                // UL can be +INF
+                if ul.is_finite() {
+                    self.set(l, 4, format!("{:E}", ul));
+                }
+
+                self.set(l, 5, format!("{:E}", stats.avg));
+                self.set(l, 6, format!("{:E}", stats.std_dev));
+                self.set(l, 7, format!("{:.2}", stats.cpk));
+            }
+        }
+    }
+
+    fn board_column(
+        &mut self,
+        DMC: &str,
+        time: u64,
+        result: BResult,
+        results: &[Option<TResult>],
+        _marginal: &[bool],
+    ) {
+        if self.vertical {
+            let l = self.next;
+
+            self.set(l, 0, DMC.to_string());
+            self.set(l, 1, u64_to_string(time));
+            self.set(l, 2, result_string(&self.result_strings, result));
+
+            for (i, res) in results.iter().enumerate() {
+                if let Some(res) = res {
+                    let c = i * 2 + 3;
+                    self.set(l, c, result_string(&self.result_strings, res.0));
+                    self.set(l, c + 1, format!("{:E}", res.1));
+                }
+            }
+
+            self.next += 1;
+        } else {
+            let c = self.next;
+
+            self.set(0, c, DMC.to_string());
+            self.set(2, c, result_string(&self.result_strings, result));
+            self.set(2, c + 1, u64_to_string(time));
+
+            for (i, res) in results.iter().enumerate() {
+                if let Some(res) = res {
+                    self.set(3 + i, c, result_string(&self.result_strings, res.0));
+                    self.set(3 + i, c + 1, format!("{:E}", res.1));
+                }
+            }
+
+            self.next += 2;
+        }
+    }
+
+    fn save(&mut self, path: &Path) -> io::Result<()> {
+        let mut lines: Vec<String> = Vec::with_capacity(self.cells.len() + 1);
+        lines.push(format!("# {}", self.product_id));
+
+        // In the vertical layout the first 7 rows are the test names, types, limits and statistics
+        let comment_rows = if self.vertical { 7 } else { 0 };
+
+        for (i, line) in self.cells.iter().enumerate() {
+            let fields: Vec<String> = line.iter().map(|f| csv_field(f)).collect();
+            if i < comment_rows {
+                lines.push(format!("# {}", fields.join(",")));
+            } else {
+                lines.push(fields.join(","));
+            }
+        }
+
+        let mut text = lines.join("\n");
+        text.push('\n');
+        std::fs::write(path, text)
+    }
+}
+
+/* JSON */
+
+// { "product": ..., "tests": [{name, type, limits and statistics}], "logs": [{DMC, time, result, results}] }
+// The results of a log are in the same order as "tests", missing results are null.
+// Result strings are not localized, the output is meant for other programs.
+#[derive(Default)]
+pub struct JsonSink {
+    product_id: String,
+    tests: Vec<JsonTest>,
+    logs: Vec<JsonLog>,
+}
+
+impl JsonSink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// serde_json writes the non-finite values (NaN, INF) as null
+#[derive(Serialize)]
+struct JsonTest {
+    name: String,
+    #[serde(rename = "type")]
+    ttype: String,
+    nominal: Option<f32>,
+    lower_limit: Option<f32>,
+    upper_limit: Option<f32>,
+    average: f64,
+    std_dev: f64,
+    cpk: f32,
+}
+
+#[derive(Serialize)]
+struct JsonResult {
+    result: String,
+    value: f32,
+}
+
+#[derive(Serialize)]
+struct JsonLog {
+    DMC: String,
+    time: String,
+    result: String,
+    results: Vec<Option<JsonResult>>,
+}
+
+#[derive(Serialize)]
+struct JsonExport<'a> {
+    product: &'a str,
+    tests: &'a [JsonTest],
+    logs: &'a [JsonLog],
+}
+
+impl ExportSink for JsonSink {
+    fn header(&mut self, product_id: &str) {
+        self.product_id = product_id.to_string();
+    }
+
+    fn test_row(&mut self, _index: usize, name: &str, ttype: TType, stats: &TestStats) {
+        let bounds = stats.limits.bounds();
+
+        self.tests.push(JsonTest {
+            name: name.to_string(),
+            ttype: ttype.print(),
+            nominal: stats.limits.nominal(),
+            lower_limit: bounds.map(|b| b.0),
+            upper_limit: bounds.map(|b| b.1),
+            average: stats.avg,
+            std_dev: stats.std_dev,
+            cpk: stats.cpk,
+        });
+    }
+
+    fn board_column(
+        &mut self,
+        DMC: &str,
+        time: u64,
+        result: BResult,
+        results: &[Option<TResult>],
+        _marginal: &[bool],
+    ) {
+        let results = results
+            .iter()
+            .map(|r| {
+                r.map(|r| JsonResult {
+                    result: r.0.print(),
+                    value: r.1,
+                })
+            })
+            .collect();
+
+        self.logs.push(JsonLog {
+            DMC: DMC.to_string(),
+            time: u64_to_string(time),
+            result: result.print(),
+            results,
+        });
+    }
+
+    fn save(&mut self, path: &Path) -> io::Result<()> {
+        let export = JsonExport {
+            product: &self.product_id,
+            tests: &self.tests,
+            logs: &self.logs,
+        };
+
+        std::fs::write(path, serde_json::to_string(&export)?)
+    }
+}
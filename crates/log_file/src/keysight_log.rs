@@ -6,7 +6,7 @@ Q:
 - BATCH -> "version label" field?
 */
 
-use std::{fs, io, path::Path, str::Chars};
+use std::{io, path::Path, str::Chars};
 
 type Result<T> = std::result::Result<T, ParsingError>;
 
@@ -87,6 +87,59 @@ pub fn status_to_str(s: i32) -> String {
     }
 }
 
+// Groups of the BTEST status codes, see status_to_str for the single codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusCategory {
+    Pass,
+    Fixture, // pin test, board handler, barcode, ConnectCheck
+    Shorts,  // shorts and pre-shorts
+    Analog,  // analog, analog cluster, polarity check
+    Testjet, // VTEP / TestJet
+    Power,
+    Digital, // digital and boundary scan
+    Functional,
+    Programming,
+    Skipped, // X'd out boards
+    Aborted, // runtime error, STOP, BREAK
+    Unknown,
+}
+
+impl StatusCategory {
+    pub fn print(&self) -> String {
+        match self {
+            StatusCategory::Pass => "Pass".to_string(),
+            StatusCategory::Fixture => "Fixture".to_string(),
+            StatusCategory::Shorts => "Shorts".to_string(),
+            StatusCategory::Analog => "Analog".to_string(),
+            StatusCategory::Testjet => "Testjet".to_string(),
+            StatusCategory::Power => "Power".to_string(),
+            StatusCategory::Digital => "Digital".to_string(),
+            StatusCategory::Functional => "Functional".to_string(),
+            StatusCategory::Programming => "Programming".to_string(),
+            StatusCategory::Skipped => "Skipped".to_string(),
+            StatusCategory::Aborted => "Aborted".to_string(),
+            StatusCategory::Unknown => "Unknown".to_string(),
+        }
+    }
+}
+
+pub fn status_category(s: i32) -> StatusCategory {
+    match s {
+        0 => StatusCategory::Pass,
+        2 | 11 | 12 | 16 => StatusCategory::Fixture,
+        4 | 10 => StatusCategory::Shorts,
+        6 | 15 | 17 => StatusCategory::Analog,
+        14 => StatusCategory::Testjet,
+        7 => StatusCategory::Power,
+        8 => StatusCategory::Digital,
+        9 => StatusCategory::Functional,
+        90 => StatusCategory::Programming,
+        13 => StatusCategory::Skipped,
+        80..=82 => StatusCategory::Aborted,
+        _ => StatusCategory::Unknown,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum KeysightPrefix {
     // {@A-???|test status|measured value (|subtest designator)}
@@ -737,7 +790,7 @@ impl TreeNode {
 }
 
 pub fn parse_file(path: &Path) -> io::Result<Vec<TreeNode>> {
-    let file = fs::read_to_string(path)?;
+    let file = crate::read_text_auto(path)?;
     let mut buffer = file.chars();
 
     let mut tree: Vec<TreeNode> = Vec::new();
@@ -1,16 +1,24 @@
 #![allow(dead_code)]
 #![allow(non_snake_case)]
 
-use std::collections::HashSet;
-use std::ffi::OsString;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
 use std::io;
 use std::ops::AddAssign;
 use std::path::{Path, PathBuf};
 
 use chrono::{Datelike, NaiveDateTime, Timelike};
-use ICT_config::{get_product_for_serial, load_gs_list_for_product, Product};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use ICT_config::{
+    export_suppressed_list, get_product_for_serial, load_gs_list_for_product,
+    first_serial_of_panel, load_suppressed_list, same_panel, Product,
+};
 
 mod keysight_log;
+pub use keysight_log::{status_category, StatusCategory};
+
+mod export;
+pub use export::*;
 
 // Removes the index from the testname.
 // For example: "17%c617" -> "c617"
@@ -28,6 +36,43 @@ fn strip_index(s: &str) -> &str {
     chars.as_str()
 }
 
+// True if 'word' is in 'text', and it is not part of a longer word.
+// For example "r12" is not found in "r123 HAS FAILED".
+fn contains_word(text: &str, word: &str) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+
+    text.match_indices(word).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + word.len()..].chars().next();
+        !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+    })
+}
+
+// Simple glob matching, '*' matches any number of characters (including none).
+// Without a '*' it is an exact match. For example "PS_Info_*" or "*Voltage*".
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let first = parts[0];
+    let last = parts[parts.len() - 1];
+    if text.len() < first.len() + last.len() || !text.starts_with(first) || !text.ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+
+    true
+}
+
 // YYMMDDhhmmss => YY.MM.DD. hh:mm:ss
 pub fn u64_to_string(mut x: u64) -> String {
     let YY = x / u64::pow(10, 10);
@@ -73,6 +118,16 @@ pub fn u64_to_time(mut x: u64) -> chrono::NaiveDateTime {
     date.and_time(time)
 }
 
+// Seconds between two YYMMDDhhmmss timestamps, 0 if either is invalid or end is before start
+fn duration_secs(start: u64, end: u64) -> u64 {
+    let parse = |x: u64| NaiveDateTime::parse_from_str(&format!("{:012}", x), "%y%m%d%H%M%S").ok();
+
+    match (parse(start), parse(end)) {
+        (Some(s), Some(e)) => (e - s).num_seconds().max(0) as u64,
+        _ => 0,
+    }
+}
+
 fn time_to_u64<T: chrono::Datelike + Timelike>(t: T) -> u64 {
     (t.year() as u64 - 2000) * u64::pow(10, 10)
         + t.month() as u64 * u64::pow(10, 8)
@@ -91,10 +146,122 @@ fn local_time_to_u64(t: chrono::DateTime<chrono::Local>) -> u64 {
         + t.second() as u64
 }
 
+// Reads a text file as UTF-8, or as WINDOWS_1252 if it is not valid UTF-8.
+// If the file starts with a BOM (UTF-8 or UTF-16), that encoding is used, and the BOM is removed.
+pub fn read_text_auto(path: &Path) -> io::Result<String> {
+    let bytes = std::fs::read(path)?;
+
+    if let Some((encoding, bom_length)) = encoding_rs::Encoding::for_bom(&bytes) {
+        println!("INFO: {} has a BOM, decoding as {}", path.display(), encoding.name());
+        let decoded = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+
+        if decoded.1 {
+            println!("ERROR: Conversion had errors");
+        }
+
+        return Ok(decoded.0.into_owned());
+    }
+
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok(text),
+        Err(e) => {
+            println!("INFO: {} is not UTF-8, decoding as WINDOWS_1252", path.display());
+            let decoded = encoding_rs::WINDOWS_1252.decode(e.as_bytes());
+
+            if decoded.2 {
+                println!("ERROR: Conversion had errors");
+            }
+
+            Ok(decoded.0.into_owned())
+        }
+    }
+}
+
+// Key for the sourcelist, so the same file reached with different path spellings is only loaded once
+fn source_key(source: &OsStr) -> OsString {
+    if let Ok(path) = std::fs::canonicalize(source) {
+        return path.into_os_string();
+    }
+
+    // Fallback, if the file can't be reached: normalize the separators and the case (Windows paths)
+    let normalized = source.to_string_lossy().replace('/', "\\");
+    OsString::from(normalized.trim_end_matches('\\').to_lowercase())
+}
+
+#[derive(PartialEq)]
+enum FCTResult {
+    Pass,
+    Fail,
+    Aborted,
+}
+
+// Result strings of the different FCT firmware versions
+fn parse_FCT_result(s: &str) -> FCTResult {
+    match s.trim().to_uppercase().as_str() {
+        "PASSED" | "PASS" | "OK" => FCTResult::Pass,
+        "ABORT" | "ABORTED" => FCTResult::Aborted,
+        _ => FCTResult::Fail,
+    }
+}
+
+// The FCT values are converted to the base unit of their TType (see From<&str> for TType)
+fn FCT_to_base_unit(value: f32, unit: &str) -> f32 {
+    match unit {
+        "mA" | "mV" | "mW" => value / 1000.0,
+        "kHZ" | "kHz" | "KHZ" => value * 1000.0,
+        _ => value,
+    }
+}
+
+// Known "Start Time" formats of the FCT stations, tried in order
+const FCT_TIME_FORMATS: [&str; 2] = ["%Y.%m.%d. %H:%M", "%Y.%m.%d. %H:%M:%S"];
+
+fn parse_FCT_time(s: &str) -> Option<chrono::NaiveDateTime> {
+    FCT_TIME_FORMATS
+        .iter()
+        .find_map(|f| chrono::NaiveDateTime::parse_from_str(s.trim(), f).ok())
+}
+
+// Linear interpolated percentile (p = 0.0..1.0) of an already sorted, non-empty slice.
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    let pos = p * (sorted.len() - 1) as f32;
+    let lo = pos.floor() as usize;
+    let hi = pos.ceil() as usize;
+
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (pos - lo as f32)
+}
+
+// Formats a DPIN subrecord as "device: node (pin), node (pin)"
+fn format_dpins(device: &str, pins: &[(String, String)]) -> String {
+    let list: Vec<String> = pins.iter().map(|(node, pin)| format!("{node} ({pin})")).collect();
+    format!("{device}: {}", list.join(", "))
+}
+
+// True if the value is closer to one of the limits than the given fraction of the limit range.
+// For one-sided limits (e.g. UL = +INF) the fraction of the finite limit itself is used.
+fn is_marginal(value: f32, limit: TLimit, fraction: f32) -> bool {
+    let (ll, ul) = match limit.bounds() {
+        Some(x) => x,
+        None => return false,
+    };
+
+    let range = ul - ll;
+    if range.is_finite() {
+        (value - ll).abs() < fraction * range || (ul - value).abs() < fraction * range
+    } else if ll.is_finite() {
+        (value - ll).abs() < fraction * ll.abs()
+    } else if ul.is_finite() {
+        (ul - value).abs() < fraction * ul.abs()
+    } else {
+        false
+    }
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum ExportMode {
     All,
     FailuresOnly,
+    FailuresAndLimitChanges,
     Manual,
 }
 
@@ -102,8 +269,12 @@ pub struct ExportSettings {
     pub vertical: bool,
     pub only_failed_panels: bool,
     pub only_final_logs: bool,
+    pub final_by_panel: bool, // With only_final_logs: export the last test of the multiboard, instead of the last log of each board
+    pub canonical_order: bool, // Export the tests in testplan order, instead of the testlist order
     pub mode: ExportMode,
     pub list: String,
+    pub result_strings: [String; 3], // Pass, Fail, Unknown
+    pub marginal_fraction: Option<f32>, // Mark passing results closer to a limit than this fraction of the limit range
 }
 
 impl Default for ExportSettings {
@@ -112,8 +283,16 @@ impl Default for ExportSettings {
             vertical: false,
             only_failed_panels: false,
             only_final_logs: false,
+            final_by_panel: false,
+            canonical_order: false,
             mode: ExportMode::All,
             list: String::new(),
+            result_strings: [
+                BResult::Pass.print(),
+                BResult::Fail.print(),
+                BResult::Unknown.print(),
+            ],
+            marginal_fraction: None,
         }
     }
 }
@@ -130,22 +309,69 @@ impl AddAssign for Yield {
     }
 }
 
-// Returns Yield as a precentage (OK/(OK+NOK))*100
 impl Yield {
+    // Returns Yield as a precentage (OK/(OK+NOK))*100, or 0.0 if there are no boards
     pub fn precentage(self) -> f32 {
+        if self.total() == 0 {
+            return 0.0;
+        }
+
         (self.0 as f32 * 100.0) / (self.0 as f32 + self.1 as f32)
     }
+
+    pub fn total(self) -> u16 {
+        self.0 + self.1
+    }
+
+    // Difference of the two yields in percentage points (self - other)
+    pub fn delta(self, other: Yield) -> f32 {
+        self.precentage() - other.precentage()
+    }
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub enum TLimit {
     #[default]
     None,
-    Lim2(f32, f32),      // UL - LL
-    Lim3(f32, f32, f32), // Nom - UL - LL
+    Lim2(
+        #[serde(with = "json_f32")] f32,
+        #[serde(with = "json_f32")] f32,
+    ), // UL - LL
+    Lim3(
+        #[serde(with = "json_f32")] f32,
+        #[serde(with = "json_f32")] f32,
+        #[serde(with = "json_f32")] f32,
+    ), // Nom - UL - LL
+}
+
+// Limits for the statistics: the strictest (LL, UL) seen, and the nominal value if there was one
+fn limits_with_nominal(nominal: Option<f32>, ll: f32, ul: f32) -> TLimit {
+    match nominal {
+        Some(nom) => TLimit::Lim3(nom, ul, ll),
+        None => TLimit::Lim2(ul, ll),
+    }
+}
+
+// Both the ICT and the FCT parser construct the limits in the order above (FCT: Lim2(max, min)).
+impl TLimit {
+    // (LL, UL), independent of the variant
+    pub fn bounds(&self) -> Option<(f32, f32)> {
+        match *self {
+            TLimit::None => None,
+            TLimit::Lim2(ul, ll) => Some((ll, ul)),
+            TLimit::Lim3(_, ul, ll) => Some((ll, ul)),
+        }
+    }
+
+    pub fn nominal(&self) -> Option<f32> {
+        match *self {
+            TLimit::Lim3(nom, _, _) => Some(nom),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TType {
     Pin,
     Shorts,
@@ -165,7 +391,9 @@ pub enum TType {
     Testjet,
     Digital,
     Measurement,
+    Voltage,
     Current,
+    Power,
     BoundaryS,
     Time,
     Frequency,
@@ -175,17 +403,38 @@ pub enum TType {
     Unknown,
 }
 
+// Known corruptions of the degree sign in FCT logs. The logs are decoded as WINDOWS_1252,
+// where 0xB0 is '°', but some stations write 0xA2 ('¢') or 0xBA ('º') instead,
+// and UTF-8 logs (0xC2 0xB0) come out as "Â°".
+const DEGREE_VARIANTS: [&str; 4] = ["Â°", "¢", "º", "˚"];
+
+// Replaces every known variant of the degree sign with '°'
+fn normalize_unit(unit: &str) -> String {
+    let mut ret = unit.trim().to_string();
+
+    for variant in DEGREE_VARIANTS {
+        ret = ret.replace(variant, "°");
+    }
+
+    if ret == "℃" {
+        ret = "°C".to_string();
+    }
+
+    ret
+}
+
 // conversion for FCT logs
 impl From<&str> for TType {
     fn from(value: &str) -> Self {
-        match value {
+        match normalize_unit(value).as_str() {
             "Ohm" => TType::Resistor,
-            "V" | "Vrms" => TType::Measurement,
+            "V" | "Vrms" | "mV" => TType::Voltage,
             "mA" | "A" => TType::Current,
+            "W" | "mW" => TType::Power,
             "Hz" | "HZ" | "kHZ" | "KHZ" => TType::Frequency,
             "%" => TType::Precentage,
             "°" => TType::Degrees,
-            "°C" | "¢C" => TType::Temperature,
+            "°C" => TType::Temperature,
             _ => TType::Unknown,
         }
     }
@@ -228,7 +477,9 @@ impl TType {
             TType::Testjet => "Testjet".to_string(),
             TType::Digital => "Digital".to_string(),
             TType::Measurement => "Measurement".to_string(),
+            TType::Voltage => "Voltage".to_string(),
             TType::Current => "Current".to_string(),
+            TType::Power => "Power".to_string(),
             TType::BoundaryS => "Boundary Scan".to_string(),
             TType::Unknown => "Unknown".to_string(),
             TType::NFet => "N-FET".to_string(),
@@ -257,7 +508,9 @@ impl TType {
             TType::Testjet => "Result".to_string(),
             TType::Digital => "Result".to_string(),
             TType::Measurement => "V".to_string(),
+            TType::Voltage => "V".to_string(),
             TType::Current => "A".to_string(),
+            TType::Power => "W".to_string(),
             TType::BoundaryS => "Result".to_string(),
             TType::Unknown => "Result".to_string(),
             TType::Time => "s".to_string(),
@@ -267,13 +520,23 @@ impl TType {
             TType::Degrees => "°".to_string(),
         }
     }
+
+    // The results of these types can span several decades, so they are easier to read on a log scale
+    pub fn prefers_log_scale(&self) -> bool {
+        matches!(
+            self,
+            TType::Resistor | TType::Capacitor | TType::Inductor | TType::Current
+        )
+    }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum BResult {
     Pass,
     Fail,
     Unknown,
+    Skipped, // Board was not tested (X'd out), it is not counted in the yields
+    Aborted, // The FCT test was aborted, it has no valid result, it is not counted in the yields
 }
 
 impl From<BResult> for bool {
@@ -320,6 +583,8 @@ impl BResult {
             BResult::Pass => String::from("Pass"),
             BResult::Fail => String::from("Fail"),
             BResult::Unknown => String::from("NA"),
+            BResult::Skipped => String::from("Skipped"),
+            BResult::Aborted => String::from("Aborted"),
         }
     }
 
@@ -328,6 +593,8 @@ impl BResult {
             BResult::Pass => egui::Color32::GREEN,
             BResult::Fail => egui::Color32::RED,
             BResult::Unknown => egui::Color32::YELLOW,
+            BResult::Skipped => egui::Color32::GRAY,
+            BResult::Aborted => egui::Color32::LIGHT_BLUE,
         }
     }
 
@@ -336,6 +603,8 @@ impl BResult {
             BResult::Pass => egui::Color32::DARK_GREEN,
             BResult::Fail => egui::Color32::RED,
             BResult::Unknown => egui::Color32::BLACK,
+            BResult::Skipped => egui::Color32::DARK_GRAY,
+            BResult::Aborted => egui::Color32::DARK_BLUE,
         }
     }
 }
@@ -346,15 +615,19 @@ pub struct FailureList {
     pub total: usize,
     pub failed: Vec<(String, u64)>,
     pub by_index: Vec<usize>,
+    pub suppressed: bool,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Test {
     name: String,
     ttype: TType,
 
+    #[serde(with = "json_result")]
     result: TResult,
     limits: TLimit,
+    #[serde(default)]
+    detail: Option<String>, // Extra info, like the failing pins of a digital test
 }
 
 impl Test {
@@ -363,6 +636,7 @@ impl Test {
         self.ttype = TType::Unknown;
         self.result = (BResult::Unknown, 0.0);
         self.limits = TLimit::None;
+        self.detail = None;
     }
 
     pub fn get_name(&self) -> &str {
@@ -380,30 +654,146 @@ impl Test {
     pub fn get_limits(&self) -> TLimit {
         self.limits
     }
+
+    pub fn get_detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+}
+
+// The source is stored as a plain path string in JSON, instead of serde's platform specific OsString format.
+fn serialize_source<S: Serializer>(source: &OsString, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&source.to_string_lossy())
+}
+
+fn deserialize_source<'de, D: Deserializer<'de>>(d: D) -> Result<OsString, D::Error> {
+    let source = String::deserialize(d)?;
+    Ok(OsString::from(source))
+}
+
+// JSON has no inf/NaN, serde_json would write them as null, and fail to read them back.
+// Finite values are written as numbers, the rest as strings ("inf", "-inf", "NaN").
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum JsonF32 {
+    Number(f32),
+    Text(String),
+}
+
+impl From<f32> for JsonF32 {
+    fn from(v: f32) -> Self {
+        if v.is_finite() {
+            JsonF32::Number(v)
+        } else {
+            JsonF32::Text(v.to_string())
+        }
+    }
+}
+
+impl JsonF32 {
+    fn into_f32<E: serde::de::Error>(self) -> Result<f32, E> {
+        match self {
+            JsonF32::Number(v) => Ok(v),
+            JsonF32::Text(s) => s
+                .parse()
+                .map_err(|_| E::custom(format!("invalid float: {s}"))),
+        }
+    }
+}
+
+mod json_f32 {
+    use super::JsonF32;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(v: &f32, s: S) -> Result<S::Ok, S::Error> {
+        JsonF32::from(*v).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<f32, D::Error> {
+        JsonF32::deserialize(d)?.into_f32()
+    }
+}
+
+mod json_result {
+    use super::{BResult, JsonF32, TResult};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(v: &TResult, s: S) -> Result<S::Ok, S::Error> {
+        (v.0, JsonF32::from(v.1)).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<TResult, D::Error> {
+        let (result, value) = <(BResult, JsonF32)>::deserialize(d)?;
+        Ok((result, value.into_f32()?))
+    }
 }
 
+// Why LogFile::load (or load_ICT/load_FCT) failed
 #[derive(Debug)]
+pub enum LogError {
+    NoTests,                 // The file was parsed, but had no tests in it
+    ParseError(String),      // The content is not a valid log
+    EmptyFile,               // The file has no content at all
+    Io(io::Error),           // The file could not be read
+    Json(serde_json::Error), // The log could not be serialized
+}
+
+impl std::fmt::Display for LogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogError::NoTests => write!(f, "Logfile contained no tests"),
+            LogError::ParseError(e) => write!(f, "Invalid log: {e}"),
+            LogError::EmptyFile => write!(f, "Logfile is empty"),
+            LogError::Io(e) => write!(f, "{e}"),
+            LogError::Json(e) => write!(f, "JSON error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for LogError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LogError::Io(e) => Some(e),
+            LogError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LogError {
+    fn from(e: io::Error) -> Self {
+        LogError::Io(e)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LogFile {
+    #[serde(serialize_with = "serialize_source", deserialize_with = "deserialize_source")]
     source: OsString,
     DMC: String,
     DMC_mb: String,
     product_id: String,
-    index: usize,
+    index: usize, // Board number as written by the tester, see LogFileHandler::push for the position
 
     result: bool,
+    aborted: bool, // Only FCT logs can be aborted, these are not counted in the yields
     status: i32,
     status_str: String,
 
     time_start: u64,
     time_end: u64,
+    bad_time_end: Option<u64>, // Original time_end, if it was before time_start
 
     tests: Vec<Test>,
     report: String,
+    failed_pins: Vec<String>,  // From the pins test
+    failed_nodes: Vec<String>, // From the shorts, testjet and digital tests
     SW_version: String,
+    #[serde(default)]
+    revision: String, // UUT type rev from the BATCH record, empty for FCT logs
 }
 
 impl LogFile {
-    pub fn load(p: &Path) -> io::Result<Self> {
+    pub fn load(p: &Path) -> Result<Self, LogError> {
         if p.extension().is_some_and(|f| f == "csv") {
             LogFile::load_FCT(p)
         } else {
@@ -411,18 +801,96 @@ impl LogFile {
         }
     }
 
-    pub fn load_FCT(p: &Path) -> io::Result<Self> {
-        println!("INFO: Loading FCT file {}", p.display());
-        let source = p.as_os_str().to_owned();
+    // Dry run of load: checks that the file could be loaded, without building the LogFile.
+    // ICT logs need a BATCH with a BTEST in it, and at least one test record in the BTEST.
+    pub fn validate(p: &Path) -> Result<(), LogError> {
+        if p.extension().is_some_and(|f| f == "csv") {
+            LogFile::validate_FCT(p)
+        } else {
+            LogFile::validate_ICT(p)
+        }
+    }
+
+    fn validate_FCT(p: &Path) -> Result<(), LogError> {
+        let file = read_text_auto(p)?;
+        if file.trim().is_empty() {
+            return Err(LogError::EmptyFile);
+        }
 
-        let file_ANSI = std::fs::read(p)?;
-        let decoded = encoding_rs::WINDOWS_1252.decode(&file_ANSI);
+        // Same rules as in load_FCT
+        let has_test = file.lines().any(|line| {
+            let tokens: Vec<&str> = line.split(';').collect();
+            if tokens.len() < 2 {
+                return false;
+            }
 
-        if decoded.2 {
-            println!("ERROR: Conversion had errors");
+            match tokens[0] {
+                "SerialNumber" | "Start Time" | "Result" | "Error Code" => false,
+                "Testing time(sec)" => tokens[1].parse::<u64>().is_ok(),
+                _ => {
+                    tokens.len() == 6
+                        && tokens[0] != "StepName"
+                        && tokens[5] != "Info"
+                        && tokens[2].parse::<f32>().is_ok()
+                }
+            }
+        });
+
+        if has_test {
+            Ok(())
+        } else {
+            Err(LogError::NoTests)
+        }
+    }
+
+    fn validate_ICT(p: &Path) -> Result<(), LogError> {
+        use keysight_log::KeysightPrefix;
+
+        let tree = keysight_log::parse_file(p)?;
+        let batch = match tree.last() {
+            Some(batch) => batch,
+            None => return Err(LogError::EmptyFile),
+        };
+
+        if !matches!(batch.data, KeysightPrefix::Batch(..)) {
+            return Err(LogError::ParseError("No BATCH field found".to_string()));
+        }
+
+        let btest = match batch.branches.last() {
+            Some(btest) if matches!(btest.data, KeysightPrefix::BTest(..)) => btest,
+            _ => return Err(LogError::ParseError("No BTEST field found".to_string())),
+        };
+
+        let has_test = btest.branches.iter().any(|t| {
+            matches!(
+                t.data,
+                KeysightPrefix::Analog(..)
+                    | KeysightPrefix::Block(..)
+                    | KeysightPrefix::Boundary(..)
+                    | KeysightPrefix::Digital(..)
+                    | KeysightPrefix::Pins(..)
+                    | KeysightPrefix::Shorts(..)
+                    | KeysightPrefix::TJet(..)
+                    | KeysightPrefix::UserDefined(..)
+            )
+        });
+
+        if has_test {
+            Ok(())
+        } else {
+            Err(LogError::NoTests)
         }
+    }
 
-        let lines = decoded.0.lines();
+    pub fn load_FCT(p: &Path) -> Result<Self, LogError> {
+        println!("INFO: Loading FCT file {}", p.display());
+        let source = p.as_os_str().to_owned();
+
+        let file = read_text_auto(p)?;
+        if file.trim().is_empty() {
+            return Err(LogError::EmptyFile);
+        }
+        let lines = file.lines();
 
         let mut DMC = None;
         //let mut DMC_mb = None;
@@ -454,13 +922,11 @@ impl LogFile {
                     }
                 }*/
                 "Start Time" => {
-                    if let Ok(time) =
-                        chrono::NaiveDateTime::parse_from_str(tokens[1], "%Y.%m.%d. %H:%M")
-                    {
+                    if let Some(time) = parse_FCT_time(tokens[1]) {
                         time_start = Some(time);
                         time_start_u64 = time_to_u64(time);
                     } else {
-                        println!("Time conversion error!");
+                        println!("Time conversion error! ({})", tokens[1]);
                     }
                 }
                 "Testing time(sec)" => {
@@ -471,6 +937,7 @@ impl LogFile {
                             ttype: TType::Time,
                             result: (BResult::Pass, dt as f32),
                             limits: TLimit::None,
+                            detail: None,
                         });
                     }
                 }
@@ -489,26 +956,15 @@ impl LogFile {
                         continue;
                     }
 
-                    if let Ok(mut meas) = tokens[2].parse::<f32>() {
-                        if tokens[4] == "mA" {
-                            meas /= 1000.0;
-                        }
-                        if tokens[4] == "kHZ" || tokens[4] == "kHz" {
-                            meas *= 1000.0;
-                        }
-
-                        let limits = if let Ok(mut min) = tokens[1].parse::<f32>() {
-                            if let Ok(mut max) = tokens[3].parse::<f32>() {
-                                if tokens[4] == "mA" {
-                                    min /= 1000.0;
-                                    max /= 1000.0;
-                                }
-                                if tokens[4] == "kHZ" || tokens[4] == "kHz" {
-                                    min *= 1000.0;
-                                    max *= 1000.0;
-                                }
+                    if let Ok(meas) = tokens[2].parse::<f32>() {
+                        let meas = FCT_to_base_unit(meas, tokens[4]);
 
-                                TLimit::Lim2(max, min)
+                        let limits = if let Ok(min) = tokens[1].parse::<f32>() {
+                            if let Ok(max) = tokens[3].parse::<f32>() {
+                                TLimit::Lim2(
+                                    FCT_to_base_unit(max, tokens[4]),
+                                    FCT_to_base_unit(min, tokens[4]),
+                                )
                             } else {
                                 TLimit::None
                             }
@@ -517,10 +973,10 @@ impl LogFile {
                         };
 
                         let result = (
-                            if tokens[5] == "Passed" {
-                                BResult::Pass
-                            } else {
-                                BResult::Fail
+                            match parse_FCT_result(tokens[5]) {
+                                FCTResult::Pass => BResult::Pass,
+                                FCTResult::Fail => BResult::Fail,
+                                FCTResult::Aborted => BResult::Unknown,
                             },
                             meas,
                         );
@@ -530,6 +986,7 @@ impl LogFile {
                             ttype: TType::from(tokens[4]),
                             result,
                             limits,
+                            detail: None,
                         });
                     }
                 }
@@ -537,10 +994,7 @@ impl LogFile {
         }
 
         if tests.is_empty() {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                "Logfile conatined no tests!",
-            ));
+            return Err(LogError::NoTests);
         }
 
         let time_end: u64 = if let Some(start) = time_start {
@@ -555,7 +1009,9 @@ impl LogFile {
         };
 
         // Generate report text for failed boards
-        let result = result.is_some_and(|f| f == "Passed");
+        let result = result.map(|f| parse_FCT_result(&f));
+        let aborted = result == Some(FCTResult::Aborted);
+        let result = result == Some(FCTResult::Pass);
         let mut report = String::new();
         if !result {
             let mut lines = Vec::new();
@@ -581,33 +1037,40 @@ impl LogFile {
         }
         
 
-        let result = LogFile {
+        let mut result = LogFile {
             source,
             DMC: DMC.clone().unwrap_or_default(),
             DMC_mb: DMC.unwrap_or_default(), //DMC_mb.unwrap_or_default(),
             product_id: "Kaized CMD".to_string(), //product_id.unwrap_or_default(),
             index: 1,
             result,
+            aborted,
             status: status.unwrap_or_default(),
             status_str: String::new(),
             time_start: time_start_u64,
             time_end,
+            bad_time_end: None,
             tests,
             report,
+            failed_pins: Vec::new(),
+            failed_nodes: Vec::new(),
             SW_version: String::new(), //SW_version.unwrap_or_default(),
+            revision: String::new(),
         };
 
+        result.check_duration();
+
         //println!("Result: {result:?}");
 
         Ok(result)
     }
 
-    pub fn load_ICT(p: &Path) -> io::Result<Self> {
+    pub fn load_ICT(p: &Path) -> Result<Self, LogError> {
         println!("INFO: Loading (v2) file {}", p.display());
         let source = p.as_os_str().to_owned();
 
         let mut product_id = String::from("NoID");
-        //let mut revision_id = String::new(); // ! New, needs to be implemented in the program
+        let mut revision_id = String::new();
 
         let mut DMC = String::from("NoDMC");
         let mut DMC_mb = String::from("NoMB");
@@ -627,6 +1090,7 @@ impl LogFile {
             ttype: TType::Pin,
             result: (BResult::Unknown, 0.0),
             limits: TLimit::None,
+            detail: None,
         });
         //
 
@@ -636,6 +1100,9 @@ impl LogFile {
         //
 
         let tree = keysight_log::parse_file(p)?;
+        if tree.is_empty() {
+            return Err(LogError::EmptyFile);
+        }
         let mut batch_node: Option<&keysight_log::TreeNode> = None;
         let mut btest_node: Option<&keysight_log::TreeNode> = None;
 
@@ -644,7 +1111,7 @@ impl LogFile {
             //      operator id|controller|testplan id|testplan rev|parent panel type|parent panel type rev (| version label)}
             if let keysight_log::KeysightPrefix::Batch(
                 p_id,
-                _, //r_id,
+                r_id,
                 _,
                 _,
                 _,
@@ -660,7 +1127,7 @@ impl LogFile {
             ) = &batch.data
             {
                 product_id = p_id.clone();
-                //revision_id = r_id.clone();
+                revision_id = r_id.clone();
                 batch_node = Some(batch);
             } else {
                 eprintln!("W: No BATCH field found!");
@@ -756,6 +1223,7 @@ impl LogFile {
                             ttype: TType::from(*analog),
                             result: (BResult::from(*status), *result),
                             limits,
+                            detail: None,
                         })
                     } else {
                         eprintln!(
@@ -764,9 +1232,62 @@ impl LogFile {
                         );
                     }
                 }
-                keysight_log::KeysightPrefix::AlarmId(_, _) => todo!(),
-                keysight_log::KeysightPrefix::Alarm(_, _, _, _, _, _, _, _, _) => todo!(),
-                keysight_log::KeysightPrefix::Array(_, _, _, _) => todo!(),
+                keysight_log::KeysightPrefix::AlarmId(time, serial) => {
+                    report.push(format!("Alarm ID: {} at {}", serial, u64_to_string(*time)));
+                }
+                // Alarms of the tester (e.g. power supply), the value is the detected value
+                keysight_log::KeysightPrefix::Alarm(
+                    alarm_type,
+                    alarm_status,
+                    time,
+                    _,
+                    _,
+                    alarm_limit,
+                    detected,
+                    controller,
+                    testhead,
+                ) => {
+                    if *alarm_status {
+                        report.push(format!(
+                            "ALARM #{} at {}: detected {}, limit {} (controller: {}, testhead: {})",
+                            alarm_type,
+                            u64_to_string(*time),
+                            detected,
+                            alarm_limit,
+                            controller,
+                            testhead
+                        ));
+                    }
+
+                    tests.push(Test {
+                        name: format!("Alarm_{}", alarm_type),
+                        ttype: TType::Unknown,
+                        result: (BResult::from(!*alarm_status), *detected as f32),
+                        limits: TLimit::None,
+                        detail: None,
+                    })
+                }
+                // The value of an array test is the failure count
+                keysight_log::KeysightPrefix::Array(sub_name, status, failures, _) => {
+                    for subfield in test.branches.iter() {
+                        match &subfield.data {
+                            keysight_log::KeysightPrefix::Report(rpt) => {
+                                report.push(rpt.clone());
+                            }
+                            _ => {
+                                eprintln!("ERR: Unhandled subfield!\n\t{:?}", subfield.data)
+                            }
+                        }
+                    }
+
+                    tests.push(Test {
+                        name: strip_index(sub_name).to_string(),
+                        ttype: TType::Measurement,
+                        result: (BResult::from(*status), *failures as f32),
+                        limits: TLimit::None,
+                        detail: None,
+                    })
+                }
                 keysight_log::KeysightPrefix::Block(b_name, _) => {
                     let block_name = strip_index(b_name).to_string();
                     let mut digital_tp: Option<usize> = None;
@@ -823,16 +1344,22 @@ impl LogFile {
                                     ttype: TType::from(*analog),
                                     result: (BResult::from(*status), *result),
                                     limits,
+                                    detail: None,
                                 })
                             }
                             keysight_log::KeysightPrefix::Digital(status, _, _, _, sub_name) => {
-                                // subrecords: DPIN - ToDo!
-
+                                let mut dpins: Vec<String> = Vec::new();
                                 for subfield in sub_test.branches.iter() {
                                     match &subfield.data {
                                         keysight_log::KeysightPrefix::Report(rpt) => {
                                             report.push(rpt.clone());
                                         }
+                                        keysight_log::KeysightPrefix::DPin(device, pins) => {
+                                            let mut tmp: Vec<String> =
+                                                pins.iter().map(|f| f.0.clone()).collect();
+                                            failed_nodes.append(&mut tmp);
+                                            dpins.push(format_dpins(device, pins));
+                                        }
                                         _ => {
                                             eprintln!(
                                                 "ERR: Unhandled subfield!\n\t{:?}",
@@ -842,10 +1369,20 @@ impl LogFile {
                                     }
                                 }
 
+                                if !dpins.is_empty() {
+                                    report.push(format!("Failed digital pins: {}", dpins.join("; ")));
+                                }
+
                                 if let Some(dt) = digital_tp {
                                     if *status != 0 {
                                         tests[dt].result = (BResult::from(*status), *status as f32);
                                     }
+                                    if !dpins.is_empty() {
+                                        if let Some(detail) = &tests[dt].detail {
+                                            dpins.insert(0, detail.clone());
+                                        }
+                                        tests[dt].detail = Some(dpins.join("; "));
+                                    }
                                 } else {
                                     digital_tp = Some(tests.len());
                                     tests.push(Test {
@@ -853,6 +1390,7 @@ impl LogFile {
                                         ttype: TType::Digital,
                                         result: (BResult::from(*status), *status as f32),
                                         limits: TLimit::None,
+                                        detail: if dpins.is_empty() { None } else { Some(dpins.join("; ")) },
                                     });
                                 }
                             }
@@ -882,6 +1420,31 @@ impl LogFile {
                                     ttype: TType::Testjet,
                                     result: (BResult::from(*status), *status as f32),
                                     limits: TLimit::None,
+                                    detail: None,
+                                })
+                            }
+                            keysight_log::KeysightPrefix::Array(sub_name, status, failures, _) => {
+                                for subfield in sub_test.branches.iter() {
+                                    match &subfield.data {
+                                        keysight_log::KeysightPrefix::Report(rpt) => {
+                                            report.push(rpt.clone());
+                                        }
+                                        _ => {
+                                            eprintln!(
+                                                "ERR: Unhandled subfield!\n\t{:?}",
+                                                subfield.data
+                                            )
+                                        }
+                                    }
+                                }
+
+                                let name = format!("{}%{}", block_name, sub_name);
+                                tests.push(Test {
+                                    name,
+                                    ttype: TType::Measurement,
+                                    result: (BResult::from(*status), *failures as f32),
+                                    limits: TLimit::None,
+                                    detail: None,
                                 })
                             }
                             keysight_log::KeysightPrefix::Boundary(sub_name, status, _, _) => {
@@ -912,6 +1475,7 @@ impl LogFile {
                                         ttype: TType::BoundaryS,
                                         result: (BResult::from(*status), *status as f32),
                                         limits: TLimit::None,
+                                        detail: None,
                                     })
                                 }
                             }
@@ -954,17 +1518,20 @@ impl LogFile {
                         ttype: TType::BoundaryS,
                         result: (BResult::from(*status), *status as f32),
                         limits: TLimit::None,
+                        detail: None,
                     })
                 }
 
                 // Digital tests can be present as a BLOCK member, or solo.
                 keysight_log::KeysightPrefix::Digital(status, _, _, _, test_name) => {
+                    let mut dpins: Vec<String> = Vec::new();
                     for subfield in test.branches.iter() {
                         match &subfield.data {
-                            keysight_log::KeysightPrefix::DPin(_, pins) => {
+                            keysight_log::KeysightPrefix::DPin(device, pins) => {
                                 let mut tmp: Vec<String> =
                                     pins.iter().map(|f| f.0.clone()).collect();
                                 failed_nodes.append(&mut tmp);
+                                dpins.push(format_dpins(device, pins));
                             }
                             keysight_log::KeysightPrefix::Report(rpt) => {
                                 report.push(rpt.clone());
@@ -975,22 +1542,28 @@ impl LogFile {
                         }
                     }
 
+                    if !dpins.is_empty() {
+                        report.push(format!("Failed digital pins: {}", dpins.join("; ")));
+                    }
+
                     tests.push(Test {
                         name: strip_index(test_name).to_string(),
                         ttype: TType::Digital,
                         result: (BResult::from(*status), *status as f32),
                         limits: TLimit::None,
+                        detail: if dpins.is_empty() { None } else { Some(dpins.join("; ")) },
                     })
                 }
                 keysight_log::KeysightPrefix::Pins(_, status, _) => {
                     // Subrecord: Pin - ToDo
+                    let mut pins: Vec<String> = Vec::new();
                     for subfield in test.branches.iter() {
                         match &subfield.data {
                             keysight_log::KeysightPrefix::Report(rpt) => {
                                 report.push(rpt.clone());
                             }
                             keysight_log::KeysightPrefix::Pin(pin) => {
-                                failed_pins.append(&mut pin.clone());
+                                pins.append(&mut pin.clone());
                             }
                             _ => {
                                 eprintln!("ERR: Unhandled subfield!\n\t{:?}", subfield.data)
@@ -998,6 +1571,11 @@ impl LogFile {
                         }
                     }
 
+                    if !pins.is_empty() {
+                        report.push(format!("Failed pins: {}", pins.join(", ")));
+                        failed_pins.append(&mut pins);
+                    }
+
                     tests[0].result = (BResult::from(*status), *status as f32);
                 }
                 keysight_log::KeysightPrefix::Report(rpt) => {
@@ -1023,6 +1601,7 @@ impl LogFile {
                         ttype: TType::Testjet,
                         result: (BResult::from(*status), *status as f32),
                         limits: TLimit::None,
+                        detail: None,
                     })
                 }
                 keysight_log::KeysightPrefix::Shorts(mut status, s1, s2, s3, _) => {
@@ -1032,11 +1611,20 @@ impl LogFile {
                         status = 1;
                     }
 
+                    // The threshold of the shorts test, only present if the testplan logs it
+                    let mut limits = TLimit::None;
+
                     for subfield in test.branches.iter() {
                         match &subfield.data {
                             keysight_log::KeysightPrefix::Report(rpt) => {
                                 report.push(rpt.clone());
                             }
+                            keysight_log::KeysightPrefix::Lim2(max, min) => {
+                                limits = TLimit::Lim2(*max, *min);
+                            }
+                            keysight_log::KeysightPrefix::Lim3(nom, max, min) => {
+                                limits = TLimit::Lim3(*nom, *max, *min);
+                            }
                             keysight_log::KeysightPrefix::ShortsSrc(_, _, node) => {
                                 failed_nodes.push(node.clone());
                                 for sub2 in &subfield.branches {
@@ -1080,7 +1668,8 @@ impl LogFile {
                         name: String::from("shorts"),
                         ttype: TType::Shorts,
                         result: (BResult::from(status), status as f32),
-                        limits: TLimit::None,
+                        limits,
+                        detail: None,
                     })
                 }
                 keysight_log::KeysightPrefix::UserDefined(s) => match s[0].as_str() {
@@ -1097,6 +1686,7 @@ impl LogFile {
                                     ttype: TType::Unknown,
                                     result: (BResult::Pass, ts as f32 / 1000.0),
                                     limits: TLimit::None,
+                                    detail: None,
                                 })
                             } else {
                                 eprintln!("ERR: Parsing error at @Programming_time!\n\t{:?}", s);
@@ -1142,15 +1732,17 @@ impl LogFile {
                         PS_counter += 1;
                         tests.push(Test {
                             name: format!("PS_Info_{PS_counter}%Voltage"),
-                            ttype: TType::Measurement,
+                            ttype: TType::Voltage,
                             result: (BResult::Pass, voltage),
                             limits: TLimit::None,
+                            detail: None,
                         });
                         tests.push(Test {
                             name: format!("PS_Info_{PS_counter}%Current"),
                             ttype: TType::Current,
                             result: (BResult::Pass, current),
                             limits: TLimit::None,
+                            detail: None,
                         });
                     }
                     x if x.starts_with("@MySW") => {
@@ -1186,6 +1778,7 @@ impl LogFile {
                 ttype: TType::Unknown,
                 result: (BResult::Fail, 0.0),
                 limits: TLimit::None,
+                detail: Some(status_category(status).print()),
             });
         }
 
@@ -1199,44 +1792,96 @@ impl LogFile {
             time_end = time_start;
         }
 
-        Ok(LogFile {
+        let mut ret = LogFile {
             source,
             DMC,
             DMC_mb,
             product_id,
             index,
             result: status == 0,
+            aborted: false,
             status,
             status_str: keysight_log::status_to_str(status),
             time_start,
             time_end,
+            bad_time_end: None,
             tests,
             report: report.join("\n"),
+            failed_pins,
+            failed_nodes,
             SW_version,
-        })
-    }
+            revision: revision_id,
+        };
 
-    pub fn is_ok(&self) -> bool {
-        !self.tests.is_empty() && self.DMC != "NoDMC" && self.DMC_mb != "NoMB"
+        ret.check_duration();
+
+        Ok(ret)
     }
 
-    pub fn has_report(&self) -> bool {
-        !self.report.is_empty()
+    // If the clock was adjusted mid-test, then time_end can be before time_start.
+    // In this case the end time is clamped to the start time, so the duration will be zero.
+    fn check_duration(&mut self) {
+        if self.time_end < self.time_start {
+            println!(
+                "W: Log of {} ends before it starts! ({} < {})",
+                self.DMC, self.time_end, self.time_start
+            );
+
+            self.bad_time_end = Some(self.time_end);
+            self.time_end = self.time_start;
+        }
     }
 
-    pub fn get_source(&self) -> &OsString {
-        &self.source
+    // ICT: from the start and end time of the BTEST record.
+    // FCT: the end time is the start time + testing time, so this is the testing time (rounded to whole seconds).
+    pub fn duration_secs(&self) -> u64 {
+        duration_secs(self.time_start, self.time_end)
     }
 
-    pub fn get_status(&self) -> i32 {
-        self.status
+    pub fn to_json(&self) -> Result<String, LogError> {
+        serde_json::to_string(self).map_err(LogError::Json)
     }
 
-    pub fn get_status_str(&self) -> &str {
-        &self.status_str
+    pub fn from_json(s: &str) -> Result<Self, LogError> {
+        serde_json::from_str(s).map_err(|e| LogError::ParseError(e.to_string()))
     }
 
-    pub fn get_product_id(&self) -> &str {
+    pub fn is_ok(&self) -> bool {
+        !self.tests.is_empty() && self.DMC != "NoDMC" && self.DMC_mb != "NoMB"
+    }
+
+    // ICT boards marked as X'd out on the panel (status 13) were not tested at all.
+    // status_str is only set for ICT logs, the FCT error codes mean something else.
+    pub fn is_skipped(&self) -> bool {
+        !self.result && !self.status_str.is_empty() && self.status == 13
+    }
+
+    // Only meaningful for ICT logs, FCT logs use their own error codes
+    pub fn get_status_category(&self) -> StatusCategory {
+        status_category(self.status)
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.aborted
+    }
+
+    pub fn has_report(&self) -> bool {
+        !self.report.is_empty()
+    }
+
+    pub fn get_source(&self) -> &OsString {
+        &self.source
+    }
+
+    pub fn get_status(&self) -> i32 {
+        self.status
+    }
+
+    pub fn get_status_str(&self) -> &str {
+        &self.status_str
+    }
+
+    pub fn get_product_id(&self) -> &str {
         &self.product_id
     }
 
@@ -1264,10 +1909,36 @@ impl LogFile {
         &self.SW_version
     }
 
+    pub fn get_revision(&self) -> &str {
+        &self.revision
+    }
+
+    pub fn get_failed_pins(&self) -> &[String] {
+        &self.failed_pins
+    }
+
+    pub fn get_failed_nodes(&self) -> &[String] {
+        &self.failed_nodes
+    }
+
     pub fn get_tests(&self) -> &Vec<Test> {
         &self.tests
     }
 
+    // Exact name match first. Tests inside a block are named "block%test",
+    // if there is no exact match, then these are matched by the part after the '%'.
+    pub fn get_test(&self, name: &str) -> Option<&Test> {
+        self.tests.iter().find(|t| t.name == name).or_else(|| {
+            self.tests
+                .iter()
+                .find(|t| t.name.contains('%') && strip_index(&t.name) == name)
+        })
+    }
+
+    pub fn get_test_value(&self, name: &str) -> Option<f32> {
+        self.get_test(name).map(|t| t.result.1)
+    }
+
     pub fn get_failed_tests(&self) -> Vec<String> {
         let mut ret = Vec::new();
 
@@ -1281,6 +1952,50 @@ impl LogFile {
 
         ret
     }
+
+    // Passing tests with a value within the fraction of the limit range to one of the limits.
+    // Uses the same rule as the marginal highlighting of the export. (name, value, limits)
+    pub fn marginal_tests(&self, fraction: f32) -> Vec<(String, f32, TLimit)> {
+        self.tests
+            .iter()
+            .filter(|t| t.result.0 == BResult::Pass && is_marginal(t.result.1, t.limits, fraction))
+            .map(|t| (t.name.clone(), t.result.1, t.limits))
+            .collect()
+    }
+
+    // Adds a calculated "PS_Info_N%Power" test (voltage * current) after each PS_Info_N%Current test.
+    // The tester doesn't measure it, so the parser leaves it out, see LogFileHandler::set_ps_power.
+    pub fn add_ps_power(&mut self) {
+        let mut tests = Vec::with_capacity(self.tests.len());
+        let mut voltage: Option<(String, f32)> = None;
+
+        for test in self.tests.drain(..) {
+            let power = if let Some(ps) = test.name.strip_suffix("%Voltage") {
+                voltage = Some((ps.to_string(), test.result.1));
+                None
+            } else if let Some(ps) = test.name.strip_suffix("%Current") {
+                voltage
+                    .as_ref()
+                    .filter(|v| v.0 == ps && ps.starts_with("PS_Info_"))
+                    .map(|v| Test {
+                        name: format!("{ps}%Power"),
+                        ttype: TType::Power,
+                        result: (BResult::Pass, v.1 * test.result.1),
+                        limits: TLimit::None,
+                        detail: None,
+                    })
+            } else {
+                None
+            };
+
+            tests.push(test);
+            if let Some(p) = power {
+                tests.push(p);
+            }
+        }
+
+        self.tests = tests;
+    }
 }
 
 struct Log {
@@ -1297,6 +2012,14 @@ struct Log {
 
 impl Log {
     fn new(log: LogFile) -> Self {
+        let result = if log.is_skipped() {
+            BResult::Skipped
+        } else if log.aborted {
+            BResult::Aborted
+        } else {
+            log.result.into()
+        };
+
         let mut results: Vec<TResult> = Vec::new();
         let mut limits: Vec<TLimit> = Vec::new();
 
@@ -1309,7 +2032,7 @@ impl Log {
             source: log.source,
             time_s: log.time_start,
             time_e: log.time_end,
-            result: log.result.into(),
+            result,
             results,
             limits,
             report: log.report,
@@ -1351,6 +2074,12 @@ impl Board {
             self.logs.push(Log::new(log));
         // b) Board is NOT empty
         } else {
+            if self.DMC != log.DMC {
+                println!(
+                    "\t\tW: Board #{} is {}, but the log is for {}!",
+                    self.index, self.DMC, log.DMC
+                );
+            }
             self.logs.push(Log::new(log));
         }
 
@@ -1377,6 +2106,10 @@ impl Board {
         for (i, log) in self.logs.iter().enumerate() {
             if log.result == BResult::Pass {
                 ret.push(format!("Log #{i} - {}: Pass\n", u64_to_string(log.time_e)));
+            } else if log.result == BResult::Skipped {
+                ret.push(format!("Log #{i} - {}: Skipped\n", u64_to_string(log.time_e)));
+            } else if log.result == BResult::Aborted {
+                ret.push(format!("Log #{i} - {}: Aborted\n", u64_to_string(log.time_e)));
             } else {
                 ret.push(format!("Log #{i} - {}: Fail\n", u64_to_string(log.time_e)));
 
@@ -1394,125 +2127,70 @@ impl Board {
         ret.join("\n")
     }
 
-    fn export_to_col(
+    // Sends the logs of the board to the sink, the results are filtered with export_list
+    // If final_time is set, then only the logs from the panel test started at that time are sent.
+    fn export(
         &self,
-        sheet: &mut rust_xlsxwriter::Worksheet,
-        mut c: u16,
+        sink: &mut dyn ExportSink,
         only_failure: bool,
         only_final: bool,
+        final_time: Option<u64>,
+        marginal_fraction: Option<f32>,
         export_list: &[usize],
-        num_format: &rust_xlsxwriter::Format,
-    ) -> u16 {
+    ) {
         if self.logs.is_empty() {
-            return c;
+            return;
         }
 
         if only_failure && self.all_ok() {
-            return c;
+            return;
         }
 
-        if only_final && only_failure && self.logs.last().is_some_and(|x| x.result == BResult::Pass)
+        if final_time.is_none()
+            && only_final
+            && only_failure
+            && self.logs.last().is_some_and(|x| x.result != BResult::Fail)
         {
-            return c;
+            return;
         }
 
-        let format_with_wrap = rust_xlsxwriter::Format::new().set_text_wrap();
-
         let log_slice = {
-            if only_final {
+            if only_final && final_time.is_none() {
                 &self.logs[self.logs.len() - 1..]
             } else {
                 &self.logs[..]
             }
         };
 
-        for l in log_slice {
-            if only_failure && l.result == BResult::Pass {
+        for log in log_slice {
+            if final_time.is_some_and(|t| t != log.time_s) {
                 continue;
             }
 
-            // DMC in a merged 2x2 range
-            let _ = sheet.merge_range(0, c, 1, c + 1, &self.DMC, &format_with_wrap);
-
-            // Log result and time of test
-            let _ = sheet.write(2, c, l.result.print());
-            let _ = sheet.write_with_format(2, c + 1, u64_to_string(l.time_s), &format_with_wrap);
-
-            let _ = sheet.set_column_width(c, 8);
-            let _ = sheet.set_column_width(c + 1, 14);
-
-            // Print measurement results
-            for (i, t) in export_list.iter().enumerate() {
-                if let Some(res) = l.results.get(*t) {
-                    if res.0 != BResult::Unknown {
-                        let _ = sheet.write(3 + i as u32, c, res.0.print());
-                        let _ =
-                            sheet.write_number_with_format(3 + i as u32, c + 1, res.1, num_format);
-                    }
-                }
-            }
-            c += 2;
-        }
-
-        c
-    }
-
-    fn export_to_line(
-        &self,
-        sheet: &mut rust_xlsxwriter::Worksheet,
-        mut l: u32,
-        only_failure: bool,
-        only_final: bool,
-        export_list: &[usize],
-        num_format: &rust_xlsxwriter::Format,
-    ) -> u32 {
-        if self.logs.is_empty() {
-            return l;
-        }
-
-        if only_failure && self.all_ok() {
-            return l;
-        }
-
-        if only_final && only_failure && self.logs.last().is_some_and(|x| x.result == BResult::Pass)
-        {
-            return l;
-        }
-
-        let log_slice = {
-            if only_final {
-                &self.logs[self.logs.len() - 1..]
-            } else {
-                &self.logs[..]
-            }
-        };
-
-        for log in log_slice {
-            if only_failure && log.result == BResult::Pass {
+            if only_failure && log.result != BResult::Fail {
                 continue;
             }
 
-            // DMC
-            let _ = sheet.write(l, 0, &self.DMC);
+            let results: Vec<Option<TResult>> = export_list
+                .iter()
+                .map(|t| log.results.get(*t).filter(|r| r.0 != BResult::Unknown).copied())
+                .collect();
 
-            // Log result and time of test
-            let _ = sheet.write(l, 2, log.result.print());
-            let _ = sheet.write(l, 1, u64_to_string(log.time_s));
-
-            // Print measurement results
-            for (i, t) in export_list.iter().enumerate() {
-                if let Some(res) = log.results.get(*t) {
-                    if res.0 != BResult::Unknown {
-                        let c = i as u16 * 2 + 3;
-                        let _ = sheet.write(l, c, res.0.print());
-                        let _ = sheet.write_number_with_format(l, c + 1, res.1, num_format);
+            let marginal: Vec<bool> = export_list
+                .iter()
+                .map(|t| {
+                    if let (Some(fraction), Some(res), Some(lim)) =
+                        (marginal_fraction, log.results.get(*t), log.limits.get(*t))
+                    {
+                        res.0 == BResult::Pass && is_marginal(res.1, *lim, fraction)
+                    } else {
+                        false
                     }
-                }
-            }
-            l += 1;
-        }
+                })
+                .collect();
 
-        l
+            sink.board_column(&self.DMC, log.time_s, log.result, &results, &marginal);
+        }
     }
 }
 
@@ -1530,6 +2208,10 @@ struct MultiBoard {
 
     // ( Start time, Multiboard test result, <Result of the individual boards>)
     results: Vec<MbResult>,
+
+    // Set on push, cleared by update. sb_yields caches the last single-board stats.
+    dirty: bool,
+    sb_yields: (Yield, Yield, Yield),
 }
 
 impl MultiBoard {
@@ -1539,6 +2221,8 @@ impl MultiBoard {
             golden_sample: false,
             boards: Vec::new(),
             results: Vec::new(),
+            dirty: true,
+            sb_yields: (Yield(0, 0), Yield(0, 0), Yield(0, 0)),
             //first_res: BResult::Unknown,
             //final_res: BResult::Unknown
         }
@@ -1554,6 +2238,7 @@ impl MultiBoard {
             self.boards.push(Board::new(self.boards.len() + 1))
         }
 
+        self.dirty = true;
         self.boards[log.index - 1].push(log)
     }
 
@@ -1561,6 +2246,53 @@ impl MultiBoard {
         self.golden_sample = true;
     }
 
+    // Moves the boards and logs of another multiboard with the same DMC into this one
+    fn merge(&mut self, other: MultiBoard) {
+        if self.DMC.is_empty() {
+            self.DMC = other.DMC;
+        }
+
+        self.golden_sample |= other.golden_sample;
+
+        for board in other.boards {
+            while self.boards.len() < board.index {
+                self.boards.push(Board::new(self.boards.len() + 1))
+            }
+
+            let own = &mut self.boards[board.index - 1];
+            if own.DMC.is_empty() {
+                own.DMC = board.DMC;
+            }
+            own.logs.extend(board.logs);
+        }
+
+        self.dirty = true;
+    }
+
+    // Start time of the last test of the multiboard
+    fn last_start(&self) -> Option<u64> {
+        self.boards.iter().filter_map(|b| b.logs.last()).map(|l| l.time_s).max()
+    }
+
+    fn export(&self, sink: &mut dyn ExportSink, settings: &ExportSettings, export_list: &[usize]) {
+        let final_time = if settings.only_final_logs && settings.final_by_panel {
+            self.last_start()
+        } else {
+            None
+        };
+
+        for b in &self.boards {
+            b.export(
+                sink,
+                settings.only_failed_panels,
+                settings.only_final_logs,
+                final_time,
+                settings.marginal_fraction,
+                export_list,
+            );
+        }
+    }
+
     // Generating stats for self, and reporting single-board stats.
     fn update(&mut self) -> (Yield, Yield, Yield) {
         let mut sb_first_yield = Yield(0, 0);
@@ -1607,7 +2339,9 @@ impl MultiBoard {
             }
         }
 
-        (sb_first_yield, sb_final_yield, sb_total_yield)
+        self.dirty = false;
+        self.sb_yields = (sb_first_yield, sb_final_yield, sb_total_yield);
+        self.sb_yields
     }
 
     fn update_results(&mut self) {
@@ -1616,8 +2350,10 @@ impl MultiBoard {
         for b in &self.boards {
             'forlog: for l in &b.logs {
                 // 1 - check if there is a results with matching "time"
+                // If the position is already filled, then it is a retest of the same board within the same second,
+                // it gets its own result instead of overwriting the first one.
                 for r in &mut self.results {
-                    if r.start == l.time_s {
+                    if r.start == l.time_s && r.panels[b.index - 1] == BResult::Unknown {
                         // write the BResult in to r.2.index
                         r.panels[b.index - 1] = l.result;
 
@@ -1646,13 +2382,17 @@ impl MultiBoard {
             let mut has_unknown = false;
             for r in &res.panels {
                 match r {
-                    BResult::Unknown => has_unknown = true,
+                    BResult::Unknown | BResult::Aborted => has_unknown = true,
                     BResult::Fail => all_ok = false,
                     _ => (),
                 }
             }
 
-            if !all_ok {
+            if res.panels.iter().all(|r| *r == BResult::Skipped) {
+                res.result = BResult::Skipped;
+            } else if res.panels.iter().all(|r| matches!(r, BResult::Skipped | BResult::Aborted)) {
+                res.result = BResult::Aborted;
+            } else if !all_ok {
                 res.result = BResult::Fail;
             } else if has_unknown {
                 res.result = BResult::Unknown;
@@ -1684,7 +2424,7 @@ impl MultiBoard {
             };
 
             for l in logs {
-                if l.result == BResult::Pass {
+                if l.result != BResult::Fail {
                     continue;
                 }
                 for (i, r) in l.results.iter().enumerate() {
@@ -1698,6 +2438,33 @@ impl MultiBoard {
         failures
     }
 
+    // Adds the pass/fail count of each test step to "counts", logs are selected the same way as in get_failures
+    fn count_test_results(&self, setting: FlSettings, counts: &mut [Yield]) {
+        for b in &self.boards {
+            if b.logs.is_empty() {
+                continue;
+            }
+
+            let logs = match setting {
+                FlSettings::All => &b.logs,
+                FlSettings::AfterRetest => &b.logs[b.logs.len() - 1..],
+                FlSettings::FirstPass => &b.logs[..1],
+            };
+
+            for l in logs {
+                for (i, r) in l.results.iter().enumerate() {
+                    if let Some(c) = counts.get_mut(i) {
+                        match r.0 {
+                            BResult::Pass => c.0 += 1,
+                            BResult::Fail => c.1 += 1,
+                            BResult::Unknown | BResult::Skipped | BResult::Aborted => {}
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     // Get the measurments for test "testid". Vec<(time, index, result, limits)>
     fn get_stats_for_test(&self, testid: usize) -> Vec<(u64, usize, TResult, TLimit)> {
         let mut resultlist: Vec<(u64, usize, TResult, TLimit)> = Vec::new();
@@ -1730,11 +2497,27 @@ pub struct LogFileHandler {
     product_id: String, // Product identifier
     product: Option<Product>,
     golden_samples: Vec<String>,
+    suppressed_tests: Vec<String>,
 
     testlist: Vec<TList>,
+    canonical_order: Vec<String>, // Test names in the order of the first complete (passing) log
+    canonical_ids: Vec<usize>, // Testlist indexes in canonical order, built by finalize
+    test_ids: HashMap<String, usize>, // Test name -> testlist index, built by finalize
+    testlist_growth: Vec<(usize, u64)>, // (testid, start time of the log) for tests added after init
+    testlist_frozen: bool, // Logs with tests not in the testlist are rejected
     multiboards: Vec<MultiBoard>,
 
     sourcelist: HashSet<OsString>,
+    bad_durations: Vec<(String, u64, u64)>, // (DMC, start, original end)
+    rejected_logs: Vec<(OsString, String)>,  // (source, reason)
+    load_errors: Vec<(PathBuf, String)>,     // (path, reason) from push_from_file_verbose
+
+    // Statistics-only mode: the results of the logs are not kept, only the running statistics.
+    stats_only: bool,
+    running_stats: Vec<RunningStats>,
+
+    // Add the calculated PS_Info power tests to the logs on push, see LogFile::add_ps_power
+    ps_power: bool,
 }
 
 #[derive(Default)]
@@ -1745,8 +2528,20 @@ pub struct HourlyYield {
     pub boards_with_gs: Yield,
 }
 
+impl AddAssign for HourlyYield {
+    fn add_assign(&mut self, x: Self) {
+        self.panels += x.panels;
+        self.panels_with_gs += x.panels_with_gs;
+        self.boards += x.boards;
+        self.boards_with_gs += x.boards_with_gs;
+    }
+}
+
 pub type HourlyStats = (u64, HourlyYield, Vec<(BResult, u64, String, bool)>); // (time, [(OK, NOK), (OK, NOK with gs)], Vec<Results>)
 pub type MbStats = (String, Vec<MbResult>, bool); // (DMC, Vec<(time, Multiboard result, Vec<Board results>)>, golden_sample)
+pub type PanelComposition = (String, Vec<(usize, String, BResult)>); // (MB DMC, Vec<(position, board DMC, final result)>)
+pub type BoardLog = (u64, BResult, Vec<(String, TResult, TLimit)>); // (start time, result, Vec<(test name, result, limits)>)
+pub type LimitChanges = (usize, String, Vec<(u64, TLimit)>); // (testid, name, Vec<(time of first appearance, limit)>)
 
 #[derive(Debug, Default)]
 pub struct TestStats {
@@ -1754,9 +2549,92 @@ pub struct TestStats {
     pub max: f32,
     pub limits: TLimit,
 
+    pub count: u32, // Number of results the statistics are based on
     pub avg: f64,
     pub std_dev: f64,
-    pub cpk: f32
+    pub cpk: f32,
+    // Long-term (overall) capability. Both use the std dev of every sample in the time range,
+    // as there are no rational subgroups to get the short-term deviation from, so they are equal for now.
+    pub ppk: f32,
+
+    pub median: f32,
+    pub q1: f32,
+    pub q3: f32,
+}
+
+// Running statistics of one test, for the statistics-only mode.
+// Uses Welford's method, so the individual results don't have to be kept.
+// Percentiles can't be computed this way, median/q1/q3 are left at default.
+#[derive(Debug, Default, Clone)]
+struct RunningStats {
+    count: u32,
+    mean: f64,
+    m2: f64,
+    min: f32,
+    max: f32,
+    limits: Option<(f32, f32)>, // (LL, UL), narrowed to the strictest limits seen
+    nominal: Option<f32>,       // First nominal value seen
+}
+
+impl RunningStats {
+    fn add(&mut self, result: TResult, limit: TLimit) {
+        if self.nominal.is_none() {
+            self.nominal = limit.nominal();
+        }
+
+        if let Some((ll, ul)) = limit.bounds() {
+            if let Some((min, max)) = self.limits.as_mut() {
+                *min = min.max(ll);
+                *max = max.min(ul);
+            } else {
+                self.limits = Some((ll, ul));
+            }
+        }
+
+        if result.0 == BResult::Unknown {
+            return;
+        }
+
+        if self.count == 0 {
+            self.min = result.1;
+            self.max = result.1;
+        }
+
+        self.min = self.min.min(result.1);
+        self.max = self.max.max(result.1);
+
+        self.count += 1;
+        let delta = result.1 as f64 - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (result.1 as f64 - self.mean);
+    }
+
+    fn get_stats(&self) -> TestStats {
+        let mut ret = TestStats {
+            min: self.min,
+            max: self.max,
+            count: self.count,
+            ..Default::default()
+        };
+
+        if let Some((min, max)) = self.limits {
+            ret.limits = limits_with_nominal(self.nominal, min, max);
+        }
+
+        if self.count > 1 {
+            ret.avg = self.mean;
+            ret.std_dev = (self.m2 / (self.count - 1) as f64).sqrt();
+
+            if let Some((min, max)) = self.limits {
+                let cpk_1 = (ret.avg - min as f64) / (3.0 * ret.std_dev);
+                let cpk_2 = (max as f64 - ret.avg) / (3.0 * ret.std_dev);
+                ret.cpk = cpk_1.min(cpk_2) as f32;
+                ret.ppk = ret.cpk;
+            }
+        }
+
+        ret
+    }
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
@@ -1766,6 +2644,46 @@ pub enum FlSettings {
     AfterRetest,
 }
 
+// How to handle the suppressed ("known bad") tests in the failure list
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SuppressMode {
+    Show,     // Keep them at their place
+    ToBottom, // Move them to the end of the list
+    Exclude,  // Remove them from the list
+}
+
+// Why a file could not be loaded by push_from_file_verbose
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),     // The file could not be read
+    Parse(LogError),   // The file was read, but it is not a valid log
+    Duplicate,         // The file was already loaded
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "IO error: {e}"),
+            LoadError::Parse(e) => write!(f, "Parsing error: {e}"),
+            LoadError::Duplicate => write!(f, "Logfile already loaded"),
+        }
+    }
+}
+
+// What is counted in a yield figure: single boards or whole panels
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CountBasis {
+    Board,
+    Panel,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum YieldStage {
+    First,
+    Final,
+    Total,
+}
+
 impl Default for LogFileHandler {
     fn default() -> Self {
         Self::new()
@@ -1785,10 +2703,58 @@ impl LogFileHandler {
             product_id: String::new(),
             product: None,
             golden_samples: Vec::new(),
+            suppressed_tests: Vec::new(),
             testlist: Vec::new(),
+            canonical_order: Vec::new(),
+            canonical_ids: Vec::new(),
+            test_ids: HashMap::new(),
+            testlist_growth: Vec::new(),
+            testlist_frozen: false,
             multiboards: Vec::new(),
             sourcelist: HashSet::new(),
+            bad_durations: Vec::new(),
+            rejected_logs: Vec::new(),
+            load_errors: Vec::new(),
+            stats_only: false,
+            running_stats: Vec::new(),
+            ps_power: false,
+        }
+    }
+
+    // Handler for long time ranges: yields and test statistics are kept,
+    // but the individual results/limits of the logs are dropped after processing.
+    // Everything that needs the individual results (failure lists, plots, export) will be empty.
+    pub fn new_stats_only() -> Self {
+        let mut ret = LogFileHandler::new();
+        ret.stats_only = true;
+        ret
+    }
+
+    pub fn is_stats_only(&self) -> bool {
+        self.stats_only
+    }
+
+    // Off by default. Set it before pushing the logs, otherwise only the later logs get the power tests.
+    pub fn set_ps_power(&mut self, enabled: bool) {
+        self.ps_power = enabled;
+    }
+
+    // In statistics-only mode, adds the results to the running statistics and drops them from the log.
+    // The tests of the log have to be in testlist order already.
+    fn accumulate_stats(&mut self, log: &mut LogFile) {
+        if !self.stats_only {
+            return;
+        }
+
+        if self.running_stats.len() < self.testlist.len() {
+            self.running_stats.resize(self.testlist.len(), RunningStats::default());
+        }
+
+        for (stat, test) in self.running_stats.iter_mut().zip(log.tests.iter()) {
+            stat.add(test.result, test.limits);
         }
+
+        log.tests.clear();
     }
 
     pub fn is_empty(&self) -> bool {
@@ -1797,22 +2763,189 @@ impl LogFileHandler {
 
     pub fn push_from_file(&mut self, p: &Path) -> bool {
         //println!("INFO: Pushing file {} into log-stack", p.display());
-        if let Ok(log) = LogFile::load(p) {
-            self.push(log)
+        match LogFile::load(p) {
+            Ok(log) => self.push(log),
+            Err(e) => {
+                println!("W: Skipping {}: {e}", p.display());
+                false
+            }
+        }
+    }
+
+    // Same as push_from_file, but the reason of the failure is returned, and saved for get_load_errors.
+    // Ok(false) means the log was loaded, but push rejected it (see get_rejected_logs).
+    pub fn push_from_file_verbose(&mut self, p: &Path) -> Result<bool, LoadError> {
+        let ret = if self.sourcelist.contains(&source_key(p.as_os_str())) {
+            Err(LoadError::Duplicate)
         } else {
-            false
+            match LogFile::load(p) {
+                Ok(log) => Ok(self.push(log)),
+                Err(LogError::Io(e)) => Err(LoadError::Io(e)),
+                Err(e) => Err(LoadError::Parse(e)),
+            }
+        };
+
+        if let Err(e) = &ret {
+            self.load_errors.push((p.to_path_buf(), e.to_string()));
+        }
+
+        ret
+    }
+
+    // (path, reason)
+    pub fn get_load_errors(&self) -> Vec<(PathBuf, String)> {
+        self.load_errors.clone()
+    }
+
+    // Parses the files on several threads, then pushes them one by one in the order of "paths",
+    // so the result doesn't depend on which thread finished first.
+    // Returns the number of logs that were actually added.
+    pub fn push_from_files(&mut self, paths: &[PathBuf]) -> usize {
+        // Files already loaded (or listed twice) are not parsed again
+        let mut keys: HashSet<OsString> = HashSet::new();
+        let to_load: Vec<&PathBuf> = paths
+            .iter()
+            .filter(|p| {
+                let key = source_key(p.as_os_str());
+                !self.sourcelist.contains(&key) && keys.insert(key)
+            })
+            .collect();
+
+        if to_load.is_empty() {
+            return 0;
+        }
+
+        let threads = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(to_load.len());
+        let chunk_size = to_load.len().div_ceil(threads);
+
+        let logs: Vec<Result<LogFile, LogError>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = to_load
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|p| LogFile::load(p))
+                            .collect::<Vec<Result<LogFile, LogError>>>()
+                    })
+                })
+                .collect();
+
+            // A panicking parser only loses its own chunk
+            handles
+                .into_iter()
+                .zip(to_load.chunks(chunk_size))
+                .flat_map(|(h, chunk)| {
+                    h.join().unwrap_or_else(|_| {
+                        chunk
+                            .iter()
+                            .map(|_| Err(LogError::ParseError("parser thread panicked".to_string())))
+                            .collect()
+                    })
+                })
+                .collect()
+        });
+
+        let mut added = 0;
+        for (path, log) in to_load.iter().zip(logs) {
+            match log {
+                Ok(log) => {
+                    if self.push(log) {
+                        added += 1;
+                    }
+                }
+                Err(e) => println!("ERR: Failed to load {}: {e}", path.display()),
+            }
+        }
+
+        added
+    }
+
+    // Loads the files one by one, on_progress is called after each with (done, total).
+    // Returns the number of logs that were actually added.
+    pub fn push_from_files_with_progress(
+        &mut self,
+        paths: &[PathBuf],
+        mut on_progress: impl FnMut(usize, usize),
+    ) -> usize {
+        let mut added = 0;
+
+        for (i, path) in paths.iter().enumerate() {
+            if self.push_from_file(path) {
+                added += 1;
+            }
+
+            on_progress(i + 1, paths.len());
+        }
+
+        added
+    }
+
+    // The board index used in the MultiBoards (and so in every per-index statistic) is the position on the panel,
+    // as given by Product::logical_position. It only differs from the tester's board number for #inv products.
+    fn logical_position(&self, board_number: usize) -> usize {
+        self.product
+            .as_ref()
+            .map_or(board_number, |p| p.logical_position(board_number))
+    }
+
+    // Panel membership with the product's boards on panel. Without a product only the exact serial matches.
+    fn is_same_panel(&self, a: &str, b: &str) -> bool {
+        let bop = self.product.as_ref().map_or(1, |p| p.get_bop());
+        same_panel(a, b, bop)
+    }
+
+    // The MultiBoards are keyed by DMC_mb. If the tester gave no panel id (DMC_mb == DMC, always the case for FCT),
+    // then the serial of the first board is calculated from the position, so the boards of a panel still group.
+    // Call after logical_position, the offset is counted from the logical position.
+    fn set_panel_id(log: &mut LogFile) {
+        if log.DMC_mb == log.DMC {
+            if let Some(first) = first_serial_of_panel(&log.DMC, log.index) {
+                log.DMC_mb = first;
+            }
+        }
+    }
+
+    // Warns if the revision of the log is not one of the accepted revisions of the product.
+    // The log is still loaded, the product list might be out of date.
+    fn check_revision(&self, log: &LogFile) {
+        if let Some(product) = &self.product {
+            if !product.check_revision(&log.revision) {
+                println!(
+                    "\t\tW: Revision {} is not listed for {}! Accepted: {:?}",
+                    log.revision,
+                    product.get_name(),
+                    product.get_revisions()
+                );
+            }
         }
     }
 
     pub fn push(&mut self, mut log: LogFile) -> bool {
         println!("\tProcessing logfile: {:?}", log.source);
 
-        if self.sourcelist.contains(&log.source) {
+        let key = source_key(&log.source);
+        if self.sourcelist.contains(&key) {
             println!("\t\tW: Logfile already loaded!");
             return false;
         }
 
-        self.sourcelist.insert(log.source.clone());
+        self.sourcelist.insert(key);
+
+        // Aborted tests are kept, but they have no valid result, so they are not counted in the yields
+        if log.aborted {
+            println!("\t\tW: Test was aborted!");
+        }
+
+        if let Some(end) = log.bad_time_end {
+            self.bad_durations.push((log.DMC.clone(), log.time_start, end));
+        }
+
+        if self.ps_power {
+            log.add_ps_power();
+        }
 
         if self.product_id.is_empty() {
             println!("\t\tINFO: Initializing as {}", log.product_id);
@@ -1823,20 +2956,31 @@ impl LogFileHandler {
                 self.product = Some(product);
             }
 
+            self.suppressed_tests = load_suppressed_list(ICT_config::SUPPRESSED_LIST);
+
+            self.check_revision(&log);
+
             println!("\t\t\tProduct is: {:?}", self.product);
             println!("\t\t\tGolden samples: {:?}", self.golden_samples);
+            println!("\t\t\tSuppressed tests: {:?}", self.suppressed_tests);
 
             // Create testlist
             for t in log.tests.iter() {
                 self.testlist.push((t.name.to_owned(), t.ttype));
             }
 
+            self.update_canonical_order(&log);
+
+            log.index = self.logical_position(log.index);
+            Self::set_panel_id(&mut log);
+
             self.multiboards.push(MultiBoard::new());
 
-            if self.golden_samples.contains(&log.DMC_mb) {
+            if self.is_golden(&log.DMC_mb) {
                 self.multiboards[0].set_gs();
             }
 
+            self.accumulate_stats(&mut log);
             self.multiboards[0].push(log)
         } else {
             // Check if it is for the same type.
@@ -1849,10 +2993,26 @@ impl LogFileHandler {
                 return false;
             }
 
-            /*
-                ToDo: Check for version (D5?)
-                Need to add version info to logfile, and product_list.
-            */
+            self.check_revision(&log);
+
+            // With a frozen testlist, logs with unknown tests are rejected instead
+            if self.testlist_frozen {
+                let unknown: Vec<&str> = log
+                    .tests
+                    .iter()
+                    .filter(|t| !self.testlist.iter().any(|e| e.0 == t.name))
+                    .map(|t| t.name.as_str())
+                    .collect();
+
+                if !unknown.is_empty() {
+                    let reason = format!("Tests not in the frozen testlist: {}", unknown.join(", "));
+                    println!("\t\tW: Log rejected! {reason}");
+                    self.rejected_logs.push((log.source.clone(), reason));
+                    return false;
+                }
+            }
+
+            self.update_canonical_order(&log);
 
             // If the testlist is missing any entries, add them
             for test in &log.tests {
@@ -1861,6 +3021,7 @@ impl LogFileHandler {
                         "\t\tW: Test {} was missing from testlist. Adding.",
                         test.name
                     );
+                    self.testlist_growth.push((self.testlist.len(), log.time_start));
                     self.testlist.push((test.name.clone(), test.ttype));
                 }
             }
@@ -1873,6 +3034,7 @@ impl LogFileHandler {
                     ttype: TType::Unknown,
                     result: (BResult::Unknown, 0.0),
                     limits: TLimit::None,
+                    detail: None,
                 },
             );
 
@@ -1907,28 +3069,35 @@ impl LogFileHandler {
                     log.tests[*i].clear();
                 }
 
+                // Name -> index in tmp. On duplicate names the last one wins.
+                let lookup: HashMap<&str, usize> = tmp
+                    .iter()
+                    .enumerate()
+                    .map(|(j, t)| (t.name.as_str(), j))
+                    .collect();
+
                 for i in &buffer_i {
-                    for t in &tmp {
-                        if self.testlist[*i].0 == t.name {
-                            log.tests[*i] = t.clone();
-                        }
+                    if let Some(j) = lookup.get(self.testlist[*i].0.as_str()) {
+                        log.tests[*i] = tmp[*j].clone();
                     }
                 }
 
                 println!("Done!");
             }
 
+            log.index = self.logical_position(log.index);
+            Self::set_panel_id(&mut log);
+            self.accumulate_stats(&mut log);
+
             // Check if the MultiBoard already exists.
-            for mb in self.multiboards.iter_mut() {
-                if mb.DMC == log.DMC_mb {
-                    return mb.push(log);
-                }
+            if let Some(i) = self.multiboards.iter().position(|mb| self.is_same_panel(&mb.DMC, &log.DMC_mb)) {
+                return self.multiboards[i].push(log);
             }
 
             // If it does not, then make a new one
             let mut mb = MultiBoard::new();
 
-            if self.golden_samples.contains(&log.DMC_mb) {
+            if self.is_golden(&log.DMC_mb) {
                 mb.set_gs();
             }
 
@@ -1938,8 +3107,185 @@ impl LogFileHandler {
         }
     }
 
+    // Adds the logs of another handler of the same product, for example one loaded on another thread.
+    // Tests missing from the testlist are appended, the results of the other handler are re-indexed to match.
+    // Logs from files which are already loaded here are dropped. Call update afterwards.
+    pub fn merge(&mut self, other: LogFileHandler) -> Result<(), String> {
+        if other.product_id.is_empty() {
+            self.sourcelist.extend(other.sourcelist);
+            self.rejected_logs.extend(other.rejected_logs);
+            self.load_errors.extend(other.load_errors);
+            return Ok(());
+        }
+
+        if !self.product_id.is_empty() && self.product_id != other.product_id {
+            return Err(format!(
+                "Product type mismatch: {} =/= {}",
+                self.product_id, other.product_id
+            ));
+        }
+
+        if self.stats_only || other.stats_only {
+            return Err("Statistics-only handlers can't be merged".to_string());
+        }
+
+        if self.testlist_frozen {
+            if let Some((name, _)) = other
+                .testlist
+                .iter()
+                .find(|(name, _)| !self.testlist.iter().any(|e| e.0 == *name))
+            {
+                return Err(format!("Test {name} is not in the frozen testlist"));
+            }
+        }
+
+        if self.product_id.is_empty() {
+            self.product_id = other.product_id;
+            self.product = other.product;
+            self.golden_samples = other.golden_samples;
+            self.suppressed_tests = other.suppressed_tests;
+        }
+
+        if self.canonical_order.is_empty() {
+            self.canonical_order = other.canonical_order;
+        }
+
+        // Test IDs of the other handler -> test IDs here
+        let mut id_map: Vec<usize> = Vec::with_capacity(other.testlist.len());
+        if self.testlist.is_empty() {
+            // Nothing loaded here yet: the testlist and its growth are taken as they are
+            id_map.extend(0..other.testlist.len());
+            self.testlist = other.testlist;
+            self.testlist_growth = other.testlist_growth;
+        } else {
+            for (j, (name, ttype)) in other.testlist.into_iter().enumerate() {
+                if let Some(i) = self.testlist.iter().position(|e| e.0 == name) {
+                    id_map.push(i);
+                } else {
+                    // Start of the first log which has the test
+                    let first_seen = other
+                        .multiboards
+                        .iter()
+                        .flat_map(|mb| mb.boards.iter())
+                        .flat_map(|b| b.logs.iter())
+                        .filter(|l| l.results.get(j).is_some_and(|r| r.0 != BResult::Unknown))
+                        .map(|l| l.time_s)
+                        .min()
+                        .unwrap_or_default();
+
+                    println!("\t\tW: Test {name} was missing from testlist. Adding.");
+                    self.testlist_growth.push((self.testlist.len(), first_seen));
+                    id_map.push(self.testlist.len());
+                    self.testlist.push((name, ttype));
+                }
+            }
+        }
+
+        let len = self.testlist.len();
+
+        for mut mb in other.multiboards {
+            for board in mb.boards.iter_mut() {
+                board
+                    .logs
+                    .retain(|l| !self.sourcelist.contains(&source_key(&l.source)));
+
+                for log in board.logs.iter_mut() {
+                    let mut results = vec![(BResult::Unknown, 0.0); len];
+                    let mut limits = vec![TLimit::None; len];
+                    for (j, i) in id_map.iter().enumerate() {
+                        if let Some(r) = log.results.get(j) {
+                            results[*i] = *r;
+                        }
+                        if let Some(l) = log.limits.get(j) {
+                            limits[*i] = *l;
+                        }
+                    }
+                    log.results = results;
+                    log.limits = limits;
+                }
+            }
+
+            if mb.boards.iter().all(|b| b.logs.is_empty()) {
+                continue;
+            }
+
+            mb.dirty = true;
+            if let Some(i) = self.multiboards.iter().position(|x| self.is_same_panel(&x.DMC, &mb.DMC)) {
+                self.multiboards[i].merge(mb);
+            } else {
+                if self.is_golden(&mb.DMC) {
+                    mb.set_gs();
+                }
+                self.multiboards.push(mb);
+            }
+        }
+
+        self.sourcelist.extend(other.sourcelist);
+        self.bad_durations.extend(other.bad_durations);
+        self.rejected_logs.extend(other.rejected_logs);
+        self.load_errors.extend(other.load_errors);
+
+        Ok(())
+    }
+
+    // Golden samples are either in the golden_samples list, or match one of the product's patterns
+    fn is_golden(&self, DMC: &str) -> bool {
+        self.golden_samples.iter().any(|gs| gs == DMC)
+            || self.product.as_ref().is_some_and(|p| p.is_golden_serial(DMC))
+    }
+
+    // Saves the test order of the first complete log. Passing logs ran every test step,
+    // failing ones might have stopped early, so only those are used.
+    fn update_canonical_order(&mut self, log: &LogFile) {
+        if self.canonical_order.is_empty() && log.result {
+            self.canonical_order = log.tests.iter().map(|t| t.name.clone()).collect();
+            self.canonical_ids.clear();
+        }
+    }
+
+    // Call once, when loading is finished. It is independent of update, which is called repeatedly while loading.
+    // update: sorts the logs, rebuilds the panel results, the yields and the per-board statistics.
+    // finalize: builds the test name -> index map and the canonical test order, and reports the problematic logs.
+    // Tests added after finalize (auto-update) are still found, through the slower uncached path.
+    pub fn finalize(&mut self) {
+        self.test_ids.clear();
+        for (i, (name, _)) in self.testlist.iter().enumerate() {
+            self.test_ids.entry(name.clone()).or_insert(i);
+        }
+
+        self.canonical_ids = self.build_canonical_order();
+
+        if self.canonical_order.is_empty() {
+            println!("W: No passing log was found, the export uses the testlist order.");
+        }
+
+        if !self.rejected_logs.is_empty() {
+            println!("W: {} logs were rejected.", self.rejected_logs.len());
+        }
+
+        if !self.load_errors.is_empty() {
+            println!("W: {} logs failed to load.", self.load_errors.len());
+        }
+
+        if !self.bad_durations.is_empty() {
+            println!("W: {} logs end before they start.", self.bad_durations.len());
+        }
+    }
+
     pub fn update(&mut self) {
         println!("INFO: Update started...");
+        self.recompute(true);
+    }
+
+    // Only re-runs MultiBoard::update on panels that got new logs since the last update.
+    // Meant for the auto-updater, which pushes a handful of logs at a time.
+    pub fn update_incremental(&mut self) {
+        let changed = self.multiboards.iter().filter(|mb| mb.dirty).count();
+        println!("INFO: Incremental update started, {changed} panels changed...");
+        self.recompute(false);
+    }
+
+    fn recompute(&mut self, full: bool) {
         let mut mbres: Vec<(Yield, Yield, Yield)> = Vec::new();
 
         self.pp_multiboard = 1;
@@ -1948,7 +3294,11 @@ impl LogFileHandler {
         self.mb_total_yield = Yield(0, 0);
 
         for b in self.multiboards.iter_mut() {
-            mbres.push(b.update());
+            if full || b.dirty {
+                mbres.push(b.update());
+            } else {
+                mbres.push(b.sb_yields);
+            }
 
             if self.pp_multiboard < b.boards.len() {
                 self.pp_multiboard = b.boards.len();
@@ -2004,9 +3354,19 @@ impl LogFileHandler {
         self.product_id.clear();
         self.product = None;
         self.golden_samples.clear();
+        self.suppressed_tests.clear();
         self.testlist.clear();
+        self.canonical_order.clear();
+        self.canonical_ids.clear();
+        self.test_ids.clear();
+        self.testlist_growth.clear();
+        self.testlist_frozen = false;
         self.multiboards.clear();
         self.sourcelist.clear();
+        self.bad_durations.clear();
+        self.rejected_logs.clear();
+        self.load_errors.clear();
+        self.running_stats.clear();
     }
 
     pub fn get_yields(&self) -> [Yield; 3] {
@@ -2025,10 +3385,93 @@ impl LogFileHandler {
         ]
     }
 
+    pub fn get_yield(&self, basis: CountBasis, stage: YieldStage) -> Yield {
+        match (basis, stage) {
+            (CountBasis::Board, YieldStage::First) => self.sb_first_yield,
+            (CountBasis::Board, YieldStage::Final) => self.sb_final_yield,
+            (CountBasis::Board, YieldStage::Total) => self.sb_total_yield,
+            (CountBasis::Panel, YieldStage::First) => self.mb_first_yield,
+            (CountBasis::Panel, YieldStage::Final) => self.mb_final_yield,
+            (CountBasis::Panel, YieldStage::Total) => self.mb_total_yield,
+        }
+    }
+
     pub fn get_testlist(&self) -> &Vec<TList> {
         &self.testlist
     }
 
+    // Testlist indexes of the tests with the given type
+    pub fn tests_of_type(&self, ttype: TType) -> Vec<usize> {
+        self.testlist
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.1 == ttype)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // Index of the test in the testlist. On duplicate names the first one.
+    pub fn get_test_id(&self, name: &str) -> Option<usize> {
+        self.test_ids
+            .get(name)
+            .copied()
+            .or_else(|| self.testlist.iter().position(|e| e.0 == name))
+    }
+
+    // Returns the testlist indexes in testplan order.
+    // Tests not present in the first complete log go to the end, in testlist order.
+    pub fn get_canonical_order(&self) -> Vec<usize> {
+        if self.canonical_ids.len() == self.testlist.len() {
+            return self.canonical_ids.clone();
+        }
+
+        self.build_canonical_order()
+    }
+
+    fn build_canonical_order(&self) -> Vec<usize> {
+        let positions: HashMap<&str, usize> = self
+            .canonical_order
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.as_str(), i))
+            .collect();
+
+        let mut ret: Vec<usize> = (0..self.testlist.len()).collect();
+        ret.sort_by_key(|i| {
+            positions
+                .get(self.testlist[*i].0.as_str())
+                .copied()
+                .unwrap_or(usize::MAX)
+        });
+
+        ret
+    }
+
+    // Tests that were added to the testlist after the first log, ordered by the time they showed up.
+    // A group of tests with the same time usually means the testplan was changed at that point.
+    pub fn get_testlist_growth_points(&self) -> Vec<(usize, u64)> {
+        let mut ret = self.testlist_growth.clone();
+        ret.sort_by_key(|k| k.1);
+        ret
+    }
+
+    // After this, logs containing tests that are not in the current testlist are rejected by push.
+    // The first log still initializes the testlist, if the handler is empty.
+    pub fn freeze_testlist(&mut self) {
+        self.testlist_frozen = true;
+    }
+
+    // (source, reason)
+    pub fn get_rejected_logs(&self) -> Vec<(OsString, String)> {
+        self.rejected_logs.clone()
+    }
+
+    // Logs where the end time was before the start time. (DMC, start, original end)
+    // Their end time was clamped to the start time during loading.
+    pub fn get_bad_duration_logs(&self) -> Vec<(String, u64, u64)> {
+        self.bad_durations.clone()
+    }
+
     // (DMC, time, result, failed test list)
     pub fn get_failed_boards(&self) -> Vec<(String, u64, BResult, Vec<String>)> {
         let mut ret = Vec::new();
@@ -2036,20 +3479,129 @@ impl LogFileHandler {
         for mb in &self.multiboards {
             for board in &mb.boards {
                 if !board.all_ok() {
-                    for log in &board.logs {
-                        let failed_ids = log.get_failed_test_list();
-                        let mut failed_tests = Vec::new();
-                        for fail in failed_ids {
-                            if let Some(x) = self.testlist.get(fail) {
-                                failed_tests.push(x.0.clone());
-                            }
-                        }
-                        ret.push((board.DMC.clone(), log.time_e, log.result, failed_tests));
+                    self.push_failed_logs(&mut ret, &board.DMC, &board.logs);
+                }
+            }
+        }
+
+        ret
+    }
+
+    // Same as get_failed_boards, but only the logs with time_e in [start, end] are considered:
+    // boards with at least one failed log in the range, with all of their logs in the range
+    pub fn get_failed_boards_in_range(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Vec<(String, u64, BResult, Vec<String>)> {
+        let mut ret = Vec::new();
+
+        for mb in &self.multiboards {
+            for board in &mb.boards {
+                let logs: Vec<&Log> = board
+                    .logs
+                    .iter()
+                    .filter(|l| start <= l.time_e && l.time_e <= end)
+                    .collect();
+
+                if logs.iter().any(|l| l.result == BResult::Fail) {
+                    self.push_failed_logs(&mut ret, &board.DMC, logs);
+                }
+            }
+        }
+
+        ret
+    }
+
+    fn push_failed_logs<'a>(
+        &self,
+        ret: &mut Vec<(String, u64, BResult, Vec<String>)>,
+        DMC: &str,
+        logs: impl IntoIterator<Item = &'a Log>,
+    ) {
+        for log in logs {
+            let failed_ids = log.get_failed_test_list();
+            let mut failed_tests = Vec::new();
+            for fail in failed_ids {
+                if let Some(x) = self.testlist.get(fail) {
+                    failed_tests.push(x.0.clone());
+                }
+            }
+            ret.push((DMC.to_string(), log.time_e, log.result, failed_tests));
+        }
+    }
+
+    // Every log of a single board, ordered by time: (time, result, failed test list)
+    pub fn get_board_history(&self, dmc: &str) -> Vec<(u64, BResult, Vec<String>)> {
+        let mut ret = Vec::new();
+
+        if let Some(board) = self.get_sb_w_DMC(dmc) {
+            for log in &board.logs {
+                let failed_tests: Vec<String> = log
+                    .get_failed_test_list()
+                    .iter()
+                    .filter_map(|fail| self.testlist.get(*fail).map(|x| x.0.clone()))
+                    .collect();
+
+                ret.push((log.time_s, log.result, failed_tests));
+            }
+        }
+
+        ret.sort_by_key(|k| k.0);
+        ret
+    }
+
+    // Logs of the same board with identical start and end times, but loaded from different files.
+    // These are most likely copies of the same log. One entry per group, a DMC can appear more than once.
+    pub fn find_duplicate_logs(&self) -> Vec<(String, Vec<OsString>)> {
+        let mut ret: Vec<(u64, String, Vec<OsString>)> = Vec::new();
+
+        for mb in &self.multiboards {
+            for board in &mb.boards {
+                let mut groups: HashMap<(u64, u64), Vec<OsString>> = HashMap::new();
+                for log in &board.logs {
+                    let sources = groups.entry((log.time_s, log.time_e)).or_default();
+                    if !sources.contains(&log.source) {
+                        sources.push(log.source.clone());
                     }
                 }
+
+                for ((time_s, _), sources) in groups {
+                    if sources.len() > 1 {
+                        ret.push((time_s, board.DMC.clone(), sources));
+                    }
+                }
+            }
+        }
+
+        ret.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)));
+        ret.into_iter().map(|(_, dmc, sources)| (dmc, sources)).collect()
+    }
+
+    // Every log of a single board, with the results of all tests in testlist order
+    pub fn get_logs_for_DMC(&self, dmc: &str) -> Vec<BoardLog> {
+        let mut ret: Vec<BoardLog> = Vec::new();
+
+        if let Some(board) = self.get_sb_w_DMC(dmc) {
+            for log in &board.logs {
+                let tests = self
+                    .testlist
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (name, _))| {
+                        (
+                            name.clone(),
+                            log.results.get(i).copied().unwrap_or((BResult::Unknown, 0.0)),
+                            log.limits.get(i).copied().unwrap_or_default(),
+                        )
+                    })
+                    .collect();
+
+                ret.push((log.time_s, log.result, tests));
             }
         }
 
+        ret.sort_by_key(|k| k.0);
         ret
     }
 
@@ -2074,6 +3626,7 @@ impl LogFileHandler {
                     total: 1,
                     failed: vec![(failure.2, failure.3)],
                     by_index: vec![0; self.pp_multiboard],
+                    suppressed: self.is_suppressed(failure.0),
                 };
 
                 new_fail.by_index[failure.1 - 1] += 1;
@@ -2091,7 +3644,171 @@ impl LogFileHandler {
         failure_list
     }
 
+    // Pass/fail count of every test step in the testlist: (test id, name, Yield)
+    pub fn get_test_yields(&self, setting: FlSettings) -> Vec<(usize, String, Yield)> {
+        let mut counts = vec![Yield(0, 0); self.testlist.len()];
+
+        for mb in &self.multiboards {
+            mb.count_test_results(setting, &mut counts);
+        }
+
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, y)| (i, self.testlist[i].0.clone(), y))
+            .collect()
+    }
+
+    // Same as get_failures, but the suppressed tests are handled according to "mode".
+    // Suppression only changes the list, the pass/fail data of the logs is untouched.
+    pub fn get_failures_w_suppression(
+        &self,
+        setting: FlSettings,
+        mode: SuppressMode,
+    ) -> Vec<FailureList> {
+        let mut failure_list = self.get_failures(setting);
+
+        match mode {
+            SuppressMode::Show => {}
+            SuppressMode::ToBottom => failure_list.sort_by_key(|k| k.suppressed), // stable, keeps the order
+            SuppressMode::Exclude => failure_list.retain(|k| !k.suppressed),
+        }
+
+        failure_list
+    }
+
+    pub fn is_suppressed(&self, testid: usize) -> bool {
+        self.testlist
+            .get(testid)
+            .is_some_and(|t| self.suppressed_tests.contains(&t.0))
+    }
+
+    // Adds or removes the test from the suppressed list, and saves the list.
+    // Returns false if the list could not be saved.
+    pub fn set_suppressed(&mut self, testid: usize, suppressed: bool) -> bool {
+        if let Some((name, _)) = self.testlist.get(testid) {
+            if suppressed {
+                if !self.suppressed_tests.contains(name) {
+                    self.suppressed_tests.push(name.clone());
+                }
+            } else {
+                self.suppressed_tests.retain(|t| t != name);
+            }
+        } else {
+            return false;
+        }
+
+        if let Err(e) = export_suppressed_list(&self.suppressed_tests) {
+            println!("ERR: Could not save the suppressed test list! {e}");
+            return false;
+        }
+
+        true
+    }
+
+    // The boards of each panel, with the result of their last log: (MB DMC, Vec<(position, board DMC, final result)>)
+    // Positions without logs are left out
+    pub fn get_panel_composition(&self) -> Vec<PanelComposition> {
+        let mut ret = Vec::new();
+
+        for mb in &self.multiboards {
+            let mut boards = Vec::new();
+
+            for sb in &mb.boards {
+                if let Some(log) = sb.logs.last() {
+                    boards.push((sb.index, sb.DMC.clone(), log.result));
+                }
+            }
+
+            ret.push((mb.DMC.clone(), boards));
+        }
+
+        ret
+    }
+
+    // Panels where the latest run still has positions without a result: (MB DMC, missing positions)
+    // Positions go from 1, the expected count comes from the product, or the largest panel seen.
+    pub fn get_incomplete_panels(&self) -> Vec<(String, Vec<usize>)> {
+        let mut ret = Vec::new();
+
+        let expected = match &self.product {
+            Some(product) => product.get_bop() as usize,
+            None => self.pp_multiboard,
+        };
+
+        for mb in &self.multiboards {
+            if let Some(latest) = mb.results.last() {
+                let expected = expected.max(latest.panels.len());
+                let missing: Vec<usize> = (0..expected)
+                    .filter(|i| {
+                        latest.panels.get(*i).copied().unwrap_or(BResult::Unknown)
+                            == BResult::Unknown
+                    })
+                    .map(|i| i + 1)
+                    .collect();
+
+                if !missing.is_empty() {
+                    ret.push((mb.DMC.clone(), missing));
+                }
+            }
+        }
+
+        ret
+    }
+
+    // Returns [position][testid] = true, if the test produced a result at the position at least once.
+    // A position missing a test which the others have run points to a fixture wiring problem.
+    pub fn test_coverage_by_position(&self) -> Vec<Vec<bool>> {
+        let positions = self
+            .multiboards
+            .iter()
+            .map(|mb| mb.boards.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut ret = vec![vec![false; self.testlist.len()]; positions];
+
+        for mb in &self.multiboards {
+            for b in &mb.boards {
+                let coverage = &mut ret[b.index - 1];
+                for log in &b.logs {
+                    for (i, r) in log.results.iter().enumerate() {
+                        if r.0 != BResult::Unknown {
+                            if let Some(c) = coverage.get_mut(i) {
+                                *c = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        ret
+    }
+
+    // (start time, test duration in seconds, result) of every log, ordered by start time
+    pub fn get_cycle_times(&self) -> Vec<(u64, u64, BResult)> {
+        let mut ret = Vec::new();
+
+        for mb in &self.multiboards {
+            for sb in &mb.boards {
+                for log in &sb.logs {
+                    ret.push((log.time_s, duration_secs(log.time_s, log.time_e), log.result));
+                }
+            }
+        }
+
+        ret.sort_by_key(|k| k.0);
+        ret
+    }
+
     pub fn get_hourly_mb_stats(&self) -> Vec<HourlyStats> {
+        // Every board of a passing panel passed, except the skipped ones
+        let passed_boards = |res: &MbResult| -> u16 {
+            let skipped = res.panels.iter().filter(|f| **f == BResult::Skipped).count();
+            self.pp_multiboard.saturating_sub(skipped) as u16
+        };
+
         // Vec<(time in yymmddhh, total ok, total nok, Vec<(result, mmss)> )>
         // Time is in format 231222154801 by default YYMMDDHHMMSS
         // We don't care about the last 4 digit, so we can div by 10^4
@@ -2100,6 +3817,10 @@ impl LogFileHandler {
 
         for mb in &self.multiboards {
             'resfor: for res in &mb.results {
+                if res.result == BResult::Aborted {
+                    continue;
+                }
+
                 let time = res.end / u64::pow(10, 4);
                 let time_2 = res.end % u64::pow(10, 4);
 
@@ -2109,12 +3830,13 @@ impl LogFileHandler {
                 for r in &mut ret {
                     if r.0 == time {
                         if res.result == BResult::Pass {
+                            let passed_boards = passed_boards(res);
                             r.1.panels_with_gs.0 += 1;
-                            r.1.boards_with_gs.0 += self.pp_multiboard as u16;
+                            r.1.boards_with_gs.0 += passed_boards;
 
                             if !mb.golden_sample {
                                 r.1.panels.0 += 1;
-                                r.1.boards.0 += self.pp_multiboard as u16;
+                                r.1.boards.0 += passed_boards;
                             }
                         } else {
                             let failed_boards =
@@ -2137,12 +3859,13 @@ impl LogFileHandler {
 
                 let mut hourly = HourlyYield::default();
                 if res.result == BResult::Pass {
+                    let passed_boards = passed_boards(res);
                     hourly.panels_with_gs.0 += 1;
-                    hourly.boards_with_gs.0 += self.pp_multiboard as u16;
+                    hourly.boards_with_gs.0 += passed_boards;
 
                     if !mb.golden_sample {
                         hourly.panels.0 += 1;
-                        hourly.boards.0 += self.pp_multiboard as u16;
+                        hourly.boards.0 += passed_boards;
                     }
                 } else {
                     let failed_boards =
@@ -2174,6 +3897,37 @@ impl LogFileHandler {
         ret
     }
 
+    // The hourly stats summed up for the shifts: (date of the shift start, shift, yields)
+    // shift_starts is [morning, afternoon, night] (Config::get_shift_starts), the shifts are 1: morning-afternoon,
+    // 2: afternoon-night, 3: night-morning. The night shift belongs to the day it started on,
+    // so the hours after midnight are counted to the previous day, same as the "Shift" button in the GUI.
+    pub fn get_shift_stats(&self, shift_starts: [u32; 3]) -> Vec<(chrono::NaiveDate, u8, HourlyYield)> {
+        let mut ret: Vec<(chrono::NaiveDate, u8, HourlyYield)> = Vec::new();
+        let [morning, afternoon, night] = shift_starts;
+
+        for (time, hourly, _) in self.get_hourly_mb_stats() {
+            let t = u64_to_time(time * u64::pow(10, 4));
+            let hour = t.hour();
+            let (date, shift) = if (morning..afternoon).contains(&hour) {
+                (t.date(), 1)
+            } else if (afternoon..night).contains(&hour) {
+                (t.date(), 2)
+            } else if hour < morning {
+                (t.date().pred_opt().unwrap_or(t.date()), 3)
+            } else {
+                (t.date(), 3)
+            };
+
+            if let Some(r) = ret.iter_mut().find(|r| r.0 == date && r.1 == shift) {
+                r.2 += hourly;
+            } else {
+                ret.push((date, shift, hourly));
+            }
+        }
+
+        ret
+    }
+
     // Returns the result of eaxh mb. Format: (DMC, Vec<(test_time, mb_result, Vec<board_result>)>)
     pub fn get_mb_results(&self) -> Vec<MbStats> {
         let mut ret: Vec<MbStats> = Vec::new();
@@ -2188,34 +3942,41 @@ impl LogFileHandler {
 
     // Calculate statistics for test "testid"
     pub fn get_statistics_for_test(&self, testid: usize) -> TestStats {
+        self.get_statistics_for_test_opts(testid, true)
+    }
+
+    // Same as get_statistics_for_test, but the golden sample multiboards can be left out,
+    // they are retested constantly and would skew the statistics.
+    // In statistics-only mode the golden samples can't be separated, include_gs is ignored.
+    pub fn get_statistics_for_test_opts(&self, testid: usize, include_gs: bool) -> TestStats {
+        if self.stats_only {
+            return self
+                .running_stats
+                .get(testid)
+                .map(|r| r.get_stats())
+                .unwrap_or_default();
+        }
+
         let mut ret = TestStats::default();
 
         let mut sum: f64 = 0.0;
         let mut count: u32 = 0;
         let mut limits: Option<(f32,f32)> = None;
+        let mut nominal: Option<f32> = None;
+        let mut values: Vec<f32> = Vec::new();
 
-        for mb in &self.multiboards {
+        for mb in self.multiboards.iter().filter(|mb| include_gs || !mb.golden_sample) {
             for sb in &mb.boards {
                 for log in &sb.logs {
-                    if let Some(limit) = log.limits.get(testid) {
-                        match limit {
-                            TLimit::None => {},
-                            TLimit::Lim2(ul, ll) => {
-                                if let Some((min, max)) = limits.as_mut() {
-                                    *min = min.max(*ll);
-                                    *max = max.min(*ul);
-                                } else {
-                                    limits = Some((*ll,*ul));
-                                }
-                            },
-                            TLimit::Lim3(_, ul, ll) => {
-                                if let Some((min, max)) = limits.as_mut() {
-                                    *min = min.max(*ll);
-                                    *max = max.min(*ul);
-                                } else {
-                                    limits = Some((*ll,*ul));
-                                }
-                            },
+                    if nominal.is_none() {
+                        nominal = log.limits.get(testid).and_then(|l| l.nominal());
+                    }
+                    if let Some((ll, ul)) = log.limits.get(testid).and_then(|l| l.bounds()) {
+                        if let Some((min, max)) = limits.as_mut() {
+                            *min = min.max(ll);
+                            *max = max.min(ul);
+                        } else {
+                            limits = Some((ll, ul));
                         }
                     }
                     if let Some(result) = log.results.get(testid) {
@@ -2230,6 +3991,7 @@ impl LogFileHandler {
 
                             sum += result.1 as f64;
                             count += 1;
+                            values.push(result.1);
                         }
                     }
                 }
@@ -2237,16 +3999,18 @@ impl LogFileHandler {
         }
 
         if let Some((min, max)) = limits {
-            ret.limits = TLimit::Lim2(max, min);
+            ret.limits = limits_with_nominal(nominal, min, max);
         }
 
+        ret.count = count;
+
         if count > 1 {
 
             ret.avg = sum / count as f64;
 
             // Std Dev:
             let mut diff_sqrd: f64 = 0.0;
-            for mb in &self.multiboards {
+            for mb in self.multiboards.iter().filter(|mb| include_gs || !mb.golden_sample) {
                 for sb in &mb.boards {
                     for log in &sb.logs {
                         if let Some(result) = log.results.get(testid) {
@@ -2264,12 +4028,53 @@ impl LogFileHandler {
                 let cpk_1 = (ret.avg - min as f64) / (3.0*ret.std_dev);
                 let cpk_2 = (max as f64 - ret.avg) / (3.0*ret.std_dev);
                 ret.cpk = cpk_1.min(cpk_2) as f32;
+                ret.ppk = ret.cpk;
             }
+
+            // Median and quartiles:
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            ret.median = percentile(&values, 0.5);
+            ret.q1 = percentile(&values, 0.25);
+            ret.q3 = percentile(&values, 0.75);
         }
 
         ret
     }
 
+    // Repeatability of the measurement, based on the golden samples.
+    // Each golden sample is measured repeatedly, so the spread of a board's results is the measurement error.
+    // Returns the pooled standard deviation of the boards, None if no board was measured at least twice.
+    pub fn gs_repeatability(&self, testid: usize) -> Option<f32> {
+        let mut diff_sqrd: f64 = 0.0;
+        let mut dof: usize = 0; // degrees of freedom
+
+        for mb in self.multiboards.iter().filter(|mb| mb.golden_sample) {
+            for sb in &mb.boards {
+                let results: Vec<f64> = sb
+                    .logs
+                    .iter()
+                    .filter_map(|log| log.results.get(testid))
+                    .filter(|r| r.0 != BResult::Unknown)
+                    .map(|r| r.1 as f64)
+                    .collect();
+
+                if results.len() < 2 {
+                    continue;
+                }
+
+                let avg = results.iter().sum::<f64>() / results.len() as f64;
+                diff_sqrd += results.iter().map(|r| (r - avg).powi(2)).sum::<f64>();
+                dof += results.len() - 1;
+            }
+        }
+
+        if dof == 0 {
+            return None;
+        }
+
+        Some((diff_sqrd / dof as f64).sqrt() as f32)
+    }
+
     // Get the measurments for test "testid". (TType,Vec<(time, index, result, limits)>) The Vec is sorted by time.
     // Could pass the DMC too
     pub fn get_stats_for_test(&self, testid: usize) -> (TType, Vec<(u64, usize, TResult, TLimit)>) {
@@ -2347,6 +4152,91 @@ impl LogFileHandler {
         }
     }
 
+    // Tests which are missing from some of the boards, a sign of the test program changing during the run.
+    // A board has the test, if any of its logs ran it. X'd out boards are not counted.
+    // Boards which only have failing logs can miss the later tests too, if the testplan stops at the first failure.
+    // (testid, name, boards with the test, tested boards)
+    pub fn get_inconsistent_tests(&self) -> Vec<(usize, String, usize, usize)> {
+        let mut present = vec![0; self.testlist.len()];
+        let mut total = 0;
+
+        for mb in &self.multiboards {
+            for sb in &mb.boards {
+                let mut has_test = vec![false; self.testlist.len()];
+                let mut tested = false;
+
+                for log in sb.logs.iter().filter(|l| l.result != BResult::Skipped) {
+                    tested = true;
+                    for (i, res) in log.results.iter().enumerate().take(has_test.len()) {
+                        if res.0 != BResult::Unknown {
+                            has_test[i] = true;
+                        }
+                    }
+                }
+
+                if tested {
+                    total += 1;
+                    for (p, h) in present.iter_mut().zip(has_test) {
+                        if h {
+                            *p += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.testlist
+            .iter()
+            .zip(present)
+            .enumerate()
+            .filter(|(_, (_, p))| *p < total)
+            .map(|(i, ((name, _), p))| (i, name.clone(), p, total))
+            .collect()
+    }
+
+    // Details of the tests with limit changes, the limits are in the order they first appeared.
+    pub fn get_limit_change_details(&self) -> Vec<LimitChanges> {
+        let mut ret = Vec::new();
+
+        for (i, (tname, ttype)) in self.testlist.iter().enumerate() {
+            // These tests have no "limit" by default, skip them
+            if matches!(
+                ttype,
+                TType::BoundaryS | TType::Digital | TType::Pin | TType::Shorts | TType::Testjet
+            ) {
+                continue;
+            }
+
+            let mut limits: Vec<(u64, TLimit)> = Vec::new();
+            for mb in &self.multiboards {
+                for sb in &mb.boards {
+                    for log in &sb.logs {
+                        if let Some(limit) = log.limits.get(i) {
+                            if *limit != TLimit::None {
+                                limits.push((log.time_s, *limit));
+                            }
+                        }
+                    }
+                }
+            }
+
+            limits.sort_by_key(|k| k.0);
+
+            let mut changes: Vec<(u64, TLimit)> = Vec::new();
+            for (time, limit) in limits {
+                if !changes.iter().any(|c| c.1 == limit) {
+                    changes.push((time, limit));
+                }
+            }
+
+            if changes.len() > 1 {
+                ret.push((i, tname.clone(), changes));
+            }
+        }
+
+        ret
+    }
+
     fn get_export_list(&self, settings: &ExportSettings) -> Vec<usize> {
         let mut ret: Vec<usize> = Vec::new();
 
@@ -2359,8 +4249,32 @@ impl LogFileHandler {
                     ret.push(id.test_id);
                 }
             }
+            ExportMode::FailuresAndLimitChanges => {
+                for id in self.get_failures(FlSettings::All) {
+                    ret.push(id.test_id);
+                }
+
+                if let Some(changes) = self.get_tests_w_limit_changes() {
+                    for (id, _) in changes {
+                        ret.push(id);
+                    }
+                }
+
+                ret.sort();
+                ret.dedup();
+            }
+            // Space separated test names, a name containing '*' is a glob pattern (see glob_match)
             ExportMode::Manual => {
                 for part in settings.list.split(' ') {
+                    if part.contains('*') {
+                        for (i, (t, _)) in self.testlist.iter().enumerate() {
+                            if glob_match(part, t) && !ret.contains(&i) {
+                                ret.push(i);
+                            }
+                        }
+                        continue;
+                    }
+
                     for (i, (t, _)) in self.testlist.iter().enumerate() {
                         if *t == part {
                             ret.push(i);
@@ -2371,151 +4285,51 @@ impl LogFileHandler {
             }
         }
 
+        if settings.canonical_order {
+            let order = self.get_canonical_order();
+            ret.sort_by_key(|t| order.iter().position(|o| o == t));
+        }
+
         ret
     }
 
-    pub fn export(&self, path: PathBuf, settings: &ExportSettings) {
-        let mut book = rust_xlsxwriter::Workbook::new();
-        let sheet = book.add_worksheet();
-        let sci_format = rust_xlsxwriter::Format::new().set_align(rust_xlsxwriter::FormatAlign::Center).set_num_format("0.00E+00");
-        let center_format = rust_xlsxwriter::Format::new().set_align(rust_xlsxwriter::FormatAlign::Center).set_num_format("0.00").set_text_wrap();
-
-        if settings.vertical {
-            // Create header
-            let _ = sheet.write(0, 0, &self.product_id);
-            let _ = sheet.write(6, 0, "DMC");
-            let _ = sheet.set_column_width(0, 32);
-            let _ = sheet.write(6, 1, "Test time");
-            let _ = sheet.set_column_width(1, 18);
-            
-            let _ = sheet.write(0, 2, "Test name:");
-            let _ = sheet.write(1, 2, "Test type:");
-            let _ = sheet.write(2, 2, "Lower limit:");
-            let _ = sheet.write(3, 2, "Upper limit:");
-            let _ = sheet.write(4, 2, "Std Dev:");
-            let _ = sheet.write(5, 2, "Cpk:");
-            let _ = sheet.write(6, 2, "Log result");
-            let _ = sheet.set_column_width(2, 10);
-
-            // Generate list of teststeps to be exported
-            let export_list = self.get_export_list(settings);
-
-            // Print testlist
-            for (i, t) in export_list.iter().enumerate() {
-                let stats = self.get_statistics_for_test(*t);
-
-                let c: u16 = (i * 2 + 3).try_into().unwrap();
-
-                // Testname and type
-                let _ = sheet.merge_range(0, c, 0, c+1, &self.testlist[*t].0, &center_format);
-                let _ = sheet.merge_range(1, c, 1, c+1, &self.testlist[*t].1.print(), &center_format);
-                
-                // Merge for the next 4 rows.
-                for row in 2..6 {
-                    let _ = sheet.merge_range(row, c, row, c+1, "", &center_format);
-                }
-
-                // Limits, StdDev, Cpk
-                if let TLimit::Lim2(ul,ll) = stats.limits {
-                    let _ = sheet.write_number_with_format(2, c, ll, &sci_format);
-
-                    // UL can be +INF
-                    if ul.is_finite() {
-                        let _ = sheet.write_number_with_format(3, c, ul, &sci_format);
-                    }
-                    
-                    let _ = sheet.write_number_with_format(4, c, stats.std_dev, &sci_format);
-                    let _ = sheet.write_number_with_format(5, c, stats.cpk, &center_format);
-                }
+    // Walks the exported tests and logs once, the sink decides the output format.
+    pub fn export_to_sink(&self, sink: &mut dyn ExportSink, settings: &ExportSettings) {
+        sink.set_result_strings(&settings.result_strings);
+        sink.header(&self.product_id);
 
-                
-                let _ = sheet.write_with_format(6, c, "Result", &center_format);
-                let _ = sheet.write_with_format(6, c + 1, "Value", &center_format);
+        // Generate list of teststeps to be exported
+        let export_list = self.get_export_list(settings);
 
-                let _ = sheet.set_column_width(c, 6);
-                let _ = sheet.set_column_width(c + 1, 10);
-            }
-
-            // Print test results
-            let mut l: u32 = 7;
-            for mb in &self.multiboards {
-                for b in &mb.boards {
-                    l = b.export_to_line(
-                        sheet,
-                        l,
-                        settings.only_failed_panels,
-                        settings.only_final_logs,
-                        &export_list,
-                        &sci_format,
-                    );
-                }
-            }
-        } else {
-            // Create header
-            let _ = sheet.write(0, 0, &self.product_id);
-            let _ = sheet.write(2, 0, "Test name");
-            let _ = sheet.set_column_width(0, 22);
-
-            let _ = sheet.write(2, 1, "Test type");
-            let _ = sheet.set_column_width(1, 16);
-
-            let _ = sheet.merge_range(1, 2, 1, 3, "Test limits", &center_format);
-
-            let _ = sheet.write_with_format(2, 2, "Lower limit", &center_format);
-            let _ = sheet.set_column_width(2, 10);
-            let _ = sheet.write_with_format(2, 3, "Upper limit", &center_format);
-            let _ = sheet.set_column_width(3, 10);
-            let _ = sheet.write_with_format(2, 4, "Average", &center_format);
-            let _ = sheet.set_column_width(4, 10);
-            let _ = sheet.write_with_format(2, 5, "Std Dev", &center_format);
-            let _ = sheet.set_column_width(5, 10);
-            let _ = sheet.write_with_format(2, 6, "Cpk", &center_format);
-            let _ = sheet.set_column_width(6, 10);
-
-            // Generate list of teststeps to be exported
-            let export_list = self.get_export_list(settings);
-
-            // Print testlist
-            for (i, t) in export_list.iter().enumerate() {
-                let stats = self.get_statistics_for_test(*t);
-                let l: u32 = (i + 3).try_into().unwrap();
-                let _ = sheet.write(l, 0, &self.testlist[*t].0);
-                let _ = sheet.write(l, 1, &self.testlist[*t].1.print());
-
-                // Limits, StdDev, Cpk
-                if let TLimit::Lim2(ul,ll) = stats.limits {
-                    let _ = sheet.write_number_with_format(l, 2, ll, &sci_format);
-
-                    // UL can be +INF
-                    if ul.is_finite() {
-                        let _ = sheet.write_number_with_format(l, 3, ul, &sci_format);
-                    }
-                    
-                    let _ = sheet.write_number_with_format(l, 4, stats.avg, &sci_format);
-                    let _ = sheet.write_number_with_format(l, 5, stats.std_dev, &sci_format);
-                    let _ = sheet.write_number_with_format(l, 6, stats.cpk, &center_format);
-                }
-            }
+        // Print testlist
+        for (i, t) in export_list.iter().enumerate() {
+            let stats = self.get_statistics_for_test(*t);
+            sink.test_row(i, &self.testlist[*t].0, self.testlist[*t].1, &stats);
+        }
 
-            // Print test results
-            let mut c: u16 = 7;
-            for mb in &self.multiboards {
-                for b in &mb.boards {
-                    c = b.export_to_col(
-                        sheet,
-                        c,
-                        settings.only_failed_panels,
-                        settings.only_final_logs,
-                        &export_list,
-                        &sci_format,
-                    );
-                }
-            }
+        // Print test results
+        for mb in &self.multiboards {
+            mb.export(sink, settings, &export_list);
         }
+    }
 
-        let _ = book.save(path);
+    pub fn export(&self, path: PathBuf, settings: &ExportSettings) {
+        let mut sink = XlsxSink::new(settings.vertical);
+        self.export_to_sink(&mut sink, settings);
+        let _ = sink.save(&path);
     }
 
+    // Same as export, but writes a UTF-8 CSV file
+    pub fn export_csv(&self, path: PathBuf, settings: &ExportSettings) {
+        let mut sink = CsvSink::new(settings.vertical);
+        self.export_to_sink(&mut sink, settings);
+        if let Err(e) = sink.save(&path) {
+            println!("ERR: Failed to save CSV export: {e}");
+        }
+    }
+
+    // The MultiBoard with a board of this DMC. If no board has it (e.g. the position was not tested),
+    // then the MultiBoard of the same panel.
     fn get_mb_w_DMC(&self, DMC: &str) -> Option<&MultiBoard> {
         for mb in self.multiboards.iter() {
             for sb in &mb.boards {
@@ -2525,20 +4339,60 @@ impl LogFileHandler {
             }
         }
 
+        if let Some(mb) = self.multiboards.iter().find(|mb| self.is_same_panel(&mb.DMC, DMC)) {
+            return Some(mb);
+        }
+
         println!("Found none as {DMC}");
         None
     }
 
     fn get_sb_w_DMC(&self, DMC: &str) -> Option<&Board> {
-        for mb in self.multiboards.iter() {
-            for sb in &mb.boards {
-                if sb.DMC == DMC {
-                    return Some(sb);
+        let ret = self
+            .get_mb_w_DMC(DMC)
+            .and_then(|mb| mb.boards.iter().find(|sb| sb.DMC == DMC));
+
+        if ret.is_none() {
+            println!("Found no board as {DMC}");
+        }
+
+        ret
+    }
+
+    // Result and limits of one test, from the final log of the board
+    pub fn get_board_test_result(&self, dmc: &str, testid: usize) -> Option<(TResult, TLimit)> {
+        if let Some(board) = self.get_sb_w_DMC(dmc) {
+            if let Some(log) = board.logs.last() {
+                if let (Some(result), Some(limit)) =
+                    (log.results.get(testid), log.limits.get(testid))
+                {
+                    return Some((*result, *limit));
+                }
+            }
+        }
+
+        None
+    }
+
+    // Report of a single test from the final log of the board.
+    // The report is stored as one text per log, so it is split into blocks at the empty lines,
+    // and only the blocks mentioning the test are returned.
+    pub fn get_test_report(&self, dmc: &str, testid: usize) -> Option<String> {
+        if let (Some(board), Some(test)) = (self.get_sb_w_DMC(dmc), self.testlist.get(testid)) {
+            if let Some(log) = board.logs.last() {
+                let name = test.0.to_lowercase();
+                let blocks: Vec<&str> = log
+                    .report
+                    .split("\n\n")
+                    .filter(|b| contains_word(&b.to_lowercase(), &name))
+                    .collect();
+
+                if !blocks.is_empty() {
+                    return Some(blocks.join("\n\n"));
                 }
             }
         }
 
-        println!("Found none as {DMC}");
         None
     }
 
@@ -2572,7 +4426,833 @@ impl LogFileHandler {
         None
     }
 
+    // Reports of every failing board on the panel, with their position
+    pub fn get_panel_nok_report(&self, mb_dmc: &str) -> Option<String> {
+        if let Some(mb) = self.get_mb_w_DMC(mb_dmc) {
+            let mut ret: Vec<String> = Vec::new();
+
+            for sb in mb.boards.iter() {
+                if !sb.all_ok() {
+                    ret.push(format!("Position {} — {}\n", sb.index, sb.DMC));
+                    ret.push(sb.get_reports());
+                }
+            }
+
+            if !ret.is_empty() {
+                return Some(ret.join("\n"));
+            }
+        }
+
+        None
+    }
+
     pub fn get_product_id(&self) -> String {
         self.product_id.clone()
     }
 }
+
+// Results of one board from the different stations of the line.
+#[derive(Clone, Debug, Default)]
+pub struct BoardTraceability {
+    pub dmc: String,
+    pub ict: Option<BResult>,
+    pub spi: Option<bool>,
+    pub aoi: Option<bool>,
+}
+
+// Collects the results of the board from the loaded ICT logs, and the SPI/AOI results.
+// There is no SPI or AOI parser in this crate, so those are given by the caller as DMC -> pass maps.
+// The ICT result is the result of the final log of the board.
+pub fn merge_results(
+    ict: &LogFileHandler,
+    spi: &HashMap<String, bool>,
+    aoi: &HashMap<String, bool>,
+    dmc: &str,
+) -> BoardTraceability {
+    BoardTraceability {
+        dmc: dmc.to_string(),
+        ict: ict
+            .get_sb_w_DMC(dmc)
+            .and_then(|sb| sb.logs.last())
+            .map(|l| l.result),
+        spi: spi.get(dmc).copied(),
+        aoi: aoi.get(dmc).copied(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Writes a file in the temp directory of the test process
+    fn temp_file(name: &str, content: &[u8]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ict_log_file_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn make_test(name: &str, result: BResult, value: f32, limits: TLimit) -> Test {
+        Test {
+            name: name.to_string(),
+            ttype: TType::Resistor,
+            result: (result, value),
+            limits,
+            detail: None,
+        }
+    }
+
+    // A log as the loaders would return it, the result is derived from the tests
+    fn make_log(source: &str, dmc: &str, index: usize, tests: Vec<Test>) -> LogFile {
+        let result = tests.iter().all(|t| t.result.0 != BResult::Fail);
+
+        LogFile {
+            source: OsString::from(source),
+            DMC: dmc.to_string(),
+            DMC_mb: dmc.to_string(),
+            product_id: "PRODUCT01".to_string(),
+            index,
+            result,
+            aborted: false,
+            status: if result { 0 } else { 1 },
+            status_str: String::new(),
+            time_start: 240101120000,
+            time_end: 240101120100,
+            bad_time_end: None,
+            tests,
+            report: String::new(),
+            failed_pins: Vec::new(),
+            failed_nodes: Vec::new(),
+            SW_version: String::new(),
+            revision: String::new(),
+        }
+    }
+
+    #[test]
+    fn json_round_trip_non_finite() {
+        let mut log = make_log(
+            "1-V0L2400001001PRODUCT01.log",
+            "V0L2400001001PRODUCT01",
+            1,
+            vec![
+                make_test("r1", BResult::Pass, 10.0, TLimit::Lim2(f32::INFINITY, 5.0)),
+                make_test("r2", BResult::Fail, f32::NAN, TLimit::Lim3(1.0, 2.0, f32::NEG_INFINITY)),
+            ],
+        );
+        log.report = "r2 HAS FAILED".to_string();
+
+        let json = log.to_json().unwrap();
+        let back = LogFile::from_json(&json).unwrap();
+
+        assert_eq!(back.get_DMC(), log.get_DMC());
+        assert_eq!(back.get_time_start(), log.get_time_start());
+        assert_eq!(back.get_time_end(), log.get_time_end());
+        assert_eq!(back.get_report(), log.get_report());
+
+        assert_eq!(back.tests[0].get_limits(), TLimit::Lim2(f32::INFINITY, 5.0));
+        assert_eq!(back.tests[0].get_result(), (BResult::Pass, 10.0));
+        assert_eq!(back.tests[1].get_limits(), TLimit::Lim3(1.0, 2.0, f32::NEG_INFINITY));
+        assert_eq!(back.tests[1].get_result().0, BResult::Fail);
+        assert!(back.tests[1].get_result().1.is_nan());
+    }
+
+    // FCT log with a header for V0L2400001001PRODUCT01, and the given step lines
+    fn fct_file(start_time: &str, steps: &[&[u8]]) -> Vec<u8> {
+        let mut ret = format!(
+            "SerialNumber;V0L2400001001PRODUCT01\nStart Time;{start_time}\nTesting time(sec);30\nResult;PASSED\n\
+             StepName;Min;Value;Max;Unit;Result\n"
+        )
+        .into_bytes();
+
+        for step in steps {
+            ret.extend_from_slice(step);
+            ret.push(b'\n');
+        }
+
+        ret
+    }
+
+    #[test]
+    fn temperature_unit_variants() {
+        for unit in ["°C", "¢C", "Â°C", "℃"] {
+            assert_eq!(TType::from(unit), TType::Temperature, "{unit}");
+        }
+
+        // WINDOWS_1252: 0xB0 is '°', 0xA2 is the corrupted '¢'
+        let path = temp_file(
+            "temperature_1252.csv",
+            &fct_file("2024.01.02. 10:20", &[b"Temp1;20;25;30;\xB0C;PASSED", b"Temp2;20;25;30;\xA2C;PASSED"]),
+        );
+        let log = LogFile::load_FCT(&path).unwrap();
+        assert_eq!(log.get_test("Temp1").unwrap().get_ttype(), TType::Temperature);
+        assert_eq!(log.get_test("Temp2").unwrap().get_ttype(), TType::Temperature);
+
+        // UTF-8: the degree sign is 0xC2 0xB0
+        let path = temp_file(
+            "temperature_utf8.csv",
+            &fct_file("2024.01.02. 10:20", &[b"Temp1;20;25;30;\xC2\xB0C;PASSED"]),
+        );
+        let log = LogFile::load_FCT(&path).unwrap();
+        assert_eq!(log.get_test("Temp1").unwrap().get_ttype(), TType::Temperature);
+        assert_eq!(TType::Temperature.unit(), "°C");
+    }
+
+    // Keysight ICT log of a single board, with one passing resistor test
+    fn ict_file(dmc: &str, board: usize, start: u64, end: u64) -> String {
+        format!(
+            "{{@BATCH|PRODUCT01|A|1|1|3070|ICT|batch|operator|controller|testplan|1|panel|1\n\
+             {{@BTEST|{dmc}|00|{start}|60|0|all|0|n|n|{end}||{board}\n\
+             {{@TS|0|0|0|0}}\n\
+             {{@BLOCK|r1|00\n\
+             {{@A-RES|00|+1.0E+03\n\
+             {{@LIM2|+1.1E+03|+9.0E+02}}}}}}\n\
+             }}}}\n"
+        )
+    }
+
+    #[test]
+    fn reversed_duration_is_clamped() {
+        let dmc = "V0L2400001001PRODUCT01";
+        let path = temp_file("reversed.log", ict_file(dmc, 1, 240101120100, 240101120000).as_bytes());
+
+        let log = LogFile::load_ICT(&path).unwrap();
+        assert_eq!(log.get_time_start(), 240101120100);
+        assert_eq!(log.get_time_end(), 240101120100);
+        assert_eq!(log.duration_secs(), 0);
+
+        let mut handler = LogFileHandler::new();
+        assert!(handler.push(log));
+        assert_eq!(
+            handler.get_bad_duration_logs(),
+            vec![(dmc.to_string(), 240101120100, 240101120000)]
+        );
+    }
+
+    #[test]
+    fn fct_start_time_formats() {
+        let path = temp_file("start_minutes.csv", &fct_file("2024.01.02. 10:20", &[b"U1;4;5;6;V;PASSED"]));
+        let log = LogFile::load_FCT(&path).unwrap();
+        assert_eq!(log.get_time_start(), 240102102000);
+        assert_eq!(log.get_time_end(), 240102102030);
+
+        let path = temp_file("start_seconds.csv", &fct_file("2024.01.02. 10:20:45", &[b"U1;4;5;6;V;PASSED"]));
+        let log = LogFile::load_FCT(&path).unwrap();
+        assert_eq!(log.get_time_start(), 240102102045);
+        assert_eq!(log.get_time_end(), 240102102115);
+
+        let path = temp_file("start_invalid.csv", &fct_file("02/01/2024 10:20", &[b"U1;4;5;6;V;PASSED"]));
+        let log = LogFile::load_FCT(&path).unwrap();
+        assert_eq!(log.get_time_start(), 0);
+    }
+
+    // Boards 1..=4 of the panel starting at V0L2400001001PRODUCT01, the board at `failed` fails
+    fn push_panel(handler: &mut LogFileHandler, failed: Option<usize>) {
+        for index in 1..=4 {
+            let result = if Some(index) == failed { BResult::Fail } else { BResult::Pass };
+            let dmc = format!("V0L240{:07}PRODUCT01", 1000 + index);
+
+            let log = make_log(
+                &format!("{index}-{dmc}.log"),
+                &dmc,
+                index,
+                vec![make_test("r1", result, 1000.0, TLimit::Lim2(1100.0, 900.0))],
+            );
+            assert!(handler.push(log));
+        }
+    }
+
+    #[test]
+    fn count_basis_board_vs_panel() {
+        let mut handler = LogFileHandler::new();
+        push_panel(&mut handler, Some(3));
+        handler.update();
+
+        // One panel of 4 boards, one of the boards failed
+        let board = handler.get_yield(CountBasis::Board, YieldStage::First);
+        let panel = handler.get_yield(CountBasis::Panel, YieldStage::First);
+        assert_eq!((board.0, board.1), (3, 1));
+        assert_eq!((panel.0, panel.1), (0, 1));
+
+        let board = handler.get_yield(CountBasis::Board, YieldStage::Total);
+        let panel = handler.get_yield(CountBasis::Panel, YieldStage::Total);
+        assert_eq!(board.total(), 4);
+        assert_eq!(panel.total(), 1);
+    }
+
+    #[test]
+    fn same_file_via_two_paths_loads_once() {
+        let path = temp_file(
+            "duplicate.log",
+            ict_file("V0L2400001001PRODUCT01", 1, 240101120000, 240101120100).as_bytes(),
+        );
+        let other = path.parent().unwrap().join(".").join("duplicate.log");
+        assert_ne!(path.as_os_str(), other.as_os_str());
+
+        let mut handler = LogFileHandler::new();
+        assert!(handler.push_from_file(&path));
+        assert!(!handler.push_from_file(&other));
+        assert!(matches!(handler.push_from_file_verbose(&other), Err(LoadError::Duplicate)));
+
+        assert_eq!(handler.multiboards.len(), 1);
+        assert_eq!(handler.multiboards[0].boards[0].logs.len(), 1);
+
+        // Unreachable paths fall back to the normalized spelling
+        assert_eq!(
+            source_key(OsStr::new("X:/Logs/ICT/a.log")),
+            source_key(OsStr::new("x:\\logs\\ict\\A.log\\"))
+        );
+    }
+
+    #[test]
+    fn read_text_auto_encodings() {
+        let text = "Hőmérséklet: 25 °C";
+
+        let mut bom = vec![0xEF, 0xBB, 0xBF];
+        bom.extend_from_slice(text.as_bytes());
+        let path = temp_file("utf8_bom.txt", &bom);
+        assert_eq!(read_text_auto(&path).unwrap(), text);
+
+        let path = temp_file("utf8.txt", text.as_bytes());
+        assert_eq!(read_text_auto(&path).unwrap(), text);
+
+        // "Mérés: 25 °C" in WINDOWS_1252, 0xE9 and 0xB0 are not valid UTF-8 on their own
+        let path = temp_file("windows_1252.txt", b"M\xE9r\xE9s: 25 \xB0C");
+        assert_eq!(read_text_auto(&path).unwrap(), "Mérés: 25 °C");
+    }
+
+    // Product from a one line product list, see ICT_config::load_product_list
+    fn make_product(name: &str, line: &str) -> Product {
+        let path = temp_file(name, line.as_bytes());
+        ICT_config::load_product_list(&path, true).remove(0)
+    }
+
+    #[test]
+    fn golden_by_pattern() {
+        let mut handler = LogFileHandler::new();
+        handler.product = Some(make_product(
+            "products_golden",
+            "Golden test | 1 | missing_dir | PRODUCT01 | #golden:V0L24000099??\n",
+        ));
+
+        for dmc in ["V0L2400009901PRODUCT01", "V0L2400001001PRODUCT01"] {
+            let log = make_log(
+                &format!("1-{dmc}.log"),
+                dmc,
+                1,
+                vec![make_test("r1", BResult::Pass, 1000.0, TLimit::Lim2(1100.0, 900.0))],
+            );
+            assert!(handler.push(log));
+        }
+        handler.update();
+
+        let golden: Vec<(String, bool)> = handler
+            .get_mb_results()
+            .into_iter()
+            .map(|(dmc, _, gs)| (dmc, gs))
+            .collect();
+        assert_eq!(
+            golden,
+            vec![
+                ("V0L2400009901PRODUCT01".to_string(), true),
+                ("V0L2400001001PRODUCT01".to_string(), false)
+            ]
+        );
+    }
+
+    #[test]
+    fn fct_milli_units_are_scaled() {
+        let path = temp_file(
+            "milli_units.csv",
+            &fct_file("2024.01.02. 10:20", &[b"U1;4500;5000;5500;mV;PASSED", b"P1;100;250;400;mW;PASSED"]),
+        );
+        let log = LogFile::load_FCT(&path).unwrap();
+
+        let u1 = log.get_test("U1").unwrap();
+        assert_eq!(u1.get_ttype(), TType::Voltage);
+        assert_eq!(u1.get_result().1, 5.0);
+        assert_eq!(u1.get_limits(), TLimit::Lim2(5.5, 4.5));
+
+        let p1 = log.get_test("P1").unwrap();
+        assert_eq!(p1.get_ttype(), TType::Power);
+        assert_eq!(p1.get_result().1, 0.25);
+        assert_eq!(p1.get_limits(), TLimit::Lim2(0.4, 0.1));
+    }
+
+    #[test]
+    fn ps_power_is_opt_in() {
+        let content = "{@BATCH|PRODUCT01|A|1|1|3070|ICT|batch|operator|controller|testplan|1|panel|1\n\
+                       {@BTEST|V0L2400001001PRODUCT01|00|240101120000|60|0|all|0|n|n|240101120100||1\n\
+                       {@TS|0|0|0|0}\n\
+                       {@PS_info|5V|0.5A}\n\
+                       }}\n";
+        let path = temp_file("ps_info.log", content.as_bytes());
+
+        let log = LogFile::load_ICT(&path).unwrap();
+        assert_eq!(log.get_test_value("PS_Info_1%Voltage"), Some(5.0));
+        assert_eq!(log.get_test_value("PS_Info_1%Current"), Some(0.5));
+        assert!(log.get_test("PS_Info_1%Power").is_none());
+
+        let mut handler = LogFileHandler::new();
+        assert!(handler.push_from_file(&path));
+        assert!(!handler.get_testlist().iter().any(|t| t.0 == "PS_Info_1%Power"));
+
+        let mut handler = LogFileHandler::new();
+        handler.set_ps_power(true);
+        assert!(handler.push_from_file(&path));
+        let names: Vec<&str> = handler.get_testlist().iter().map(|t| t.0.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["pins", "shorts", "PS_Info_1%Voltage", "PS_Info_1%Current", "PS_Info_1%Power"]
+        );
+    }
+
+    #[test]
+    fn fct_utf8_sample() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/fct_utf8_bom.csv");
+        let log = LogFile::load_FCT(&path).unwrap();
+
+        assert_eq!(log.get_DMC(), "V0L2400001001PRODUCT01");
+        assert_eq!(log.get_time_start(), 240102102000);
+        assert!(log.result);
+
+        let names: Vec<&str> = log.get_tests().iter().map(|t| t.get_name()).collect();
+        assert_eq!(names, vec!["Testing time", "Tápfeszültség", "Áramfelvétel", "Hőmérséklet"]);
+
+        assert_eq!(log.get_test_value("Áramfelvétel"), Some(0.152));
+        assert_eq!(log.get_test("Hőmérséklet").unwrap().get_ttype(), TType::Temperature);
+    }
+
+    #[test]
+    fn inverted_product_4_up() {
+        let mut handler = LogFileHandler::new();
+        handler.product = Some(make_product(
+            "products_inverted",
+            "Inverted | 4 | missing_dir | PRODUCT01 | #inv\n",
+        ));
+
+        // Board number 1 of the tester is at position 4 of the panel, it fails
+        for board_number in 1..=4 {
+            let position = 5 - board_number;
+            let dmc = format!("V0L240{:07}PRODUCT01", 1000 + position);
+            let result = if board_number == 1 { BResult::Fail } else { BResult::Pass };
+
+            let log = make_log(
+                &format!("{board_number}-{dmc}.log"),
+                &dmc,
+                board_number,
+                vec![make_test("r1", result, 1000.0, TLimit::Lim2(1100.0, 900.0))],
+            );
+            assert!(handler.push(log));
+        }
+        handler.update();
+
+        let composition = handler.get_panel_composition();
+        assert_eq!(composition.len(), 1);
+        assert_eq!(composition[0].0, "V0L2400001001PRODUCT01");
+        assert_eq!(composition[0].1.len(), 4);
+
+        for (position, dmc, result) in &composition[0].1 {
+            assert_eq!(*dmc, format!("V0L240{:07}PRODUCT01", 1000 + position));
+            let expected = if *position == 4 { BResult::Fail } else { BResult::Pass };
+            assert_eq!(*result, expected);
+        }
+
+        let results = handler.get_mb_results();
+        assert_eq!(
+            results[0].1[0].panels,
+            vec![BResult::Pass, BResult::Pass, BResult::Pass, BResult::Fail]
+        );
+    }
+
+    #[test]
+    fn retests_within_the_same_second() {
+        let dmc = "V0L2400001001PRODUCT01";
+        let mut handler = LogFileHandler::new();
+
+        let first = make_log(
+            "1-first.log",
+            dmc,
+            1,
+            vec![make_test("r1", BResult::Fail, 2000.0, TLimit::Lim2(1100.0, 900.0))],
+        );
+        let mut retest = make_log(
+            "1-retest.log",
+            dmc,
+            1,
+            vec![make_test("r1", BResult::Pass, 1000.0, TLimit::Lim2(1100.0, 900.0))],
+        );
+        retest.time_end = first.time_end + 30;
+        assert_eq!(first.time_start, retest.time_start);
+
+        assert!(handler.push(first));
+        assert!(handler.push(retest));
+        handler.update();
+
+        let results = &handler.get_mb_results()[0].1;
+        let summary: Vec<(u64, u64, Vec<BResult>)> = results
+            .iter()
+            .map(|r| (r.start, r.end, r.panels.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (240101120000, 240101120100, vec![BResult::Fail]),
+                (240101120000, 240101120130, vec![BResult::Pass]),
+            ]
+        );
+
+        let board = handler.get_yield(CountBasis::Board, YieldStage::Total);
+        assert_eq!((board.0, board.1), (1, 1));
+    }
+
+    #[test]
+    fn skipped_by_status_code() {
+        let mut log = make_log("1-x.log", "V0L2400001001PRODUCT01", 1, Vec::new());
+        log.result = false;
+        log.status = 13;
+        log.status_str = keysight_log::status_to_str(13);
+        assert!(log.is_skipped());
+
+        log.status = 6;
+        log.status_str = keysight_log::status_to_str(6);
+        assert!(!log.is_skipped());
+
+        // FCT error code 13
+        log.status = 13;
+        log.status_str = String::new();
+        assert!(!log.is_skipped());
+    }
+
+    #[test]
+    fn reorder_large_testlist() {
+        const N: usize = 5000;
+        let dmc = "V0L2400001001PRODUCT01";
+
+        let tests: Vec<Test> = (0..N)
+            .map(|i| make_test(&format!("t{i}"), BResult::Pass, i as f32, TLimit::None))
+            .collect();
+
+        // Reversed order, "t0" is missing and "t1" is there twice, the last one has to win
+        let mut reversed: Vec<Test> = tests.iter().rev().cloned().collect();
+        reversed[N - 1] = make_test("t1", BResult::Pass, -1.0, TLimit::None);
+
+        let mut handler = LogFileHandler::new();
+        assert!(handler.push(make_log("1-first.log", dmc, 1, tests)));
+        assert!(handler.push(make_log("1-reversed.log", dmc, 1, reversed)));
+
+        let names: Vec<String> = handler.get_testlist().iter().map(|t| t.0.clone()).collect();
+        assert_eq!(names.len(), N);
+        assert!(names.iter().enumerate().all(|(i, name)| *name == format!("t{i}")));
+
+        let results = &handler.multiboards[0].boards[0].logs[1].results;
+        assert_eq!(results.len(), N);
+        assert_eq!(results[0].0, BResult::Unknown);
+        assert_eq!(results[1], (BResult::Pass, -1.0));
+        for (i, result) in results.iter().enumerate().skip(2) {
+            assert_eq!(*result, (BResult::Pass, i as f32));
+        }
+    }
+
+    #[test]
+    fn yield_percentage() {
+        assert_eq!(Yield(0, 0).precentage(), 0.0);
+        assert_eq!(Yield(12, 0).precentage(), 100.0);
+        assert_eq!(Yield(3, 1).precentage(), 75.0);
+        assert_eq!(Yield(0, 0).delta(Yield(0, 0)), 0.0);
+    }
+
+    #[test]
+    fn inconsistent_tests_count_boards() {
+        let lim = TLimit::Lim2(1100.0, 900.0);
+        let pass = |name: &str| make_test(name, BResult::Pass, 1000.0, lim);
+        let serial = |i: usize| format!("V0L240{:07}PRODUCT01", 1000 + i);
+
+        let mut handler = LogFileHandler::new();
+        assert!(handler.push(make_log("1.log", &serial(1), 1, vec![pass("r1"), pass("r2")])));
+        // Old program, without r2
+        assert!(handler.push(make_log("2.log", &serial(2), 2, vec![pass("r1")])));
+        // Stopped at the first failure, but the retest ran r2
+        let fail = make_test("r1", BResult::Fail, 2000.0, lim);
+        assert!(handler.push(make_log("3a.log", &serial(3), 3, vec![fail])));
+        assert!(handler.push(make_log("3b.log", &serial(3), 3, vec![pass("r1"), pass("r2")])));
+        // X'd out
+        let mut skipped = make_log("4.log", &serial(4), 4, Vec::new());
+        skipped.result = false;
+        skipped.status = 13;
+        skipped.status_str = keysight_log::status_to_str(13);
+        assert!(handler.push(skipped));
+
+        assert_eq!(handler.get_inconsistent_tests(), vec![(1, "r2".to_string(), 2, 3)]);
+    }
+
+    #[test]
+    fn json_and_csv_sinks() {
+        let stats = TestStats {
+            limits: TLimit::Lim2(f32::INFINITY, 0.5),
+            avg: f64::NAN,
+            ..Default::default()
+        };
+
+        let mut sink = JsonSink::new();
+        sink.header("PRODUCT \"01\"");
+        sink.test_row(0, "r1\\a", TType::Resistor, &stats);
+        sink.board_column(
+            "V0L2400001001PRODUCT01",
+            240101120000,
+            BResult::Pass,
+            &[Some((BResult::Pass, 1.5)), None],
+            &[false, false],
+        );
+
+        let path = temp_file("export.json", b"");
+        sink.save(&path).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+
+        assert_eq!(json["product"], "PRODUCT \"01\"");
+        assert_eq!(json["tests"][0]["name"], "r1\\a");
+        assert_eq!(json["tests"][0]["lower_limit"], 0.5);
+        assert!(json["tests"][0]["upper_limit"].is_null());
+        assert!(json["tests"][0]["average"].is_null());
+        assert_eq!(json["logs"][0]["results"][0]["value"], 1.5);
+        assert!(json["logs"][0]["results"][1].is_null());
+
+        let mut sink = CsvSink::new(false);
+        sink.header("PRODUCT01");
+        sink.test_row(0, "r1", TType::Resistor, &stats);
+
+        let path = temp_file("export.csv", b"");
+        sink.save(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.ends_with('\n') && !text.ends_with("\n\n"));
+    }
+
+    #[test]
+    fn aborted_logs_are_kept_but_not_counted() {
+        let lim = TLimit::Lim2(1100.0, 900.0);
+        let mut handler = LogFileHandler::new();
+
+        let passed = make_log(
+            "passed.csv",
+            "V0L2400001001PRODUCT01",
+            1,
+            vec![make_test("r1", BResult::Pass, 1000.0, lim)],
+        );
+        let mut aborted = make_log(
+            "aborted.csv",
+            "V0L2400001005PRODUCT01",
+            1,
+            vec![make_test("r1", BResult::Unknown, 0.0, lim)],
+        );
+        aborted.result = false;
+        aborted.aborted = true;
+
+        assert!(handler.push(passed));
+        assert!(handler.push(aborted));
+        handler.update();
+
+        assert!(handler.get_rejected_logs().is_empty());
+        let results = handler.get_mb_results();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].0, "V0L2400001005PRODUCT01");
+        assert_eq!(results[1].1[0].result, BResult::Aborted);
+
+        for stage in [YieldStage::First, YieldStage::Final, YieldStage::Total] {
+            for basis in [CountBasis::Board, CountBasis::Panel] {
+                let y = handler.get_yield(basis, stage);
+                assert_eq!((y.0, y.1), (1, 0));
+            }
+        }
+
+        let hourly = handler.get_hourly_mb_stats();
+        assert_eq!(hourly.len(), 1);
+        assert_eq!((hourly[0].1.panels.0, hourly[0].1.panels.1), (1, 0));
+    }
+
+    #[test]
+    fn lookup_by_position_offset_serial() {
+        let mut handler = LogFileHandler::new();
+        handler.product = Some(make_product("products_same_panel", "Panel test | 4 | missing_dir | PRODUCT01\n"));
+
+        // Position 3 is not tested, position 4 reports the serial of position 2 as the panel id
+        for index in [1, 2, 4] {
+            let dmc = format!("V0L240{:07}PRODUCT01", 1000 + index);
+            let result = if index == 2 { BResult::Fail } else { BResult::Pass };
+            let mut log = make_log(
+                &format!("{index}-{dmc}.log"),
+                &dmc,
+                index,
+                vec![make_test("r1", result, 1000.0, TLimit::Lim2(1100.0, 900.0))],
+            );
+            if index == 4 {
+                log.DMC_mb = "V0L2400001002PRODUCT01".to_string();
+            }
+            assert!(handler.push(log));
+        }
+        handler.update();
+
+        assert_eq!(handler.get_mb_results().len(), 1);
+
+        // Lookup by the untested position still finds the panel
+        let report = handler.get_panel_nok_report("V0L2400001003PRODUCT01").unwrap();
+        assert!(report.contains("V0L2400001002PRODUCT01"));
+        assert!(handler.get_report_for_SB_NOK("V0L2400001003PRODUCT01").is_some());
+        assert!(handler.get_report_for_SB("V0L2400001003PRODUCT01").is_none());
+
+        // The next panel is not matched
+        assert!(handler.get_panel_nok_report("V0L2400001005PRODUCT01").is_none());
+    }
+
+    #[test]
+    fn finalize_builds_test_lookups() {
+        let lim = TLimit::Lim2(1100.0, 900.0);
+        let test = |name: &str, result: BResult| make_test(name, result, 1000.0, lim);
+
+        let mut handler = LogFileHandler::new();
+        let failed = make_log(
+            "1-V0L2400001001PRODUCT01.log",
+            "V0L2400001001PRODUCT01",
+            1,
+            vec![test("r1", BResult::Fail), test("r2", BResult::Pass), test("r3", BResult::Pass)],
+        );
+        let passed = make_log(
+            "1-V0L2400001002PRODUCT01.log",
+            "V0L2400001002PRODUCT01",
+            1,
+            vec![test("r3", BResult::Pass), test("r1", BResult::Pass), test("r2", BResult::Pass)],
+        );
+        assert!(handler.push(failed));
+        assert!(handler.push(passed));
+        handler.finalize();
+        handler.update();
+
+        // The testlist follows the first log, the canonical order the first passing one
+        assert_eq!(handler.get_test_id("r3"), Some(2));
+        assert_eq!(handler.get_test_id("r9"), None);
+        assert_eq!(handler.get_canonical_order(), vec![2, 0, 1]);
+
+        // A test added after finalize is still found, and goes to the end of the canonical order
+        let grown = make_log(
+            "1-V0L2400001003PRODUCT01.log",
+            "V0L2400001003PRODUCT01",
+            1,
+            vec![test("r1", BResult::Pass), test("r2", BResult::Pass), test("r3", BResult::Pass), test("r4", BResult::Pass)],
+        );
+        assert!(handler.push(grown));
+        assert_eq!(handler.get_test_id("r4"), Some(3));
+        assert_eq!(handler.get_canonical_order(), vec![2, 0, 1, 3]);
+    }
+
+    #[test]
+    fn shift_stats_custom_starts() {
+        let mut handler = LogFileHandler::new();
+
+        // Shifts start at 7, 15 and 23
+        let ends = [240101063000, 240101073000, 240101143000, 240101153000, 240101223000, 240101233000];
+        for (i, end) in ends.into_iter().enumerate() {
+            let dmc = format!("V0L240{:07}PRODUCT01", 1001 + i);
+            let mut log = make_log(
+                &format!("1-{dmc}.log"),
+                &dmc,
+                1,
+                vec![make_test("r1", BResult::Pass, 1000.0, TLimit::Lim2(1100.0, 900.0))],
+            );
+            log.time_start = end - 100;
+            log.time_end = end;
+            assert!(handler.push(log));
+        }
+        handler.update();
+
+        let date = |d: u32| chrono::NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let mut shifts: Vec<(chrono::NaiveDate, u8, u16)> = handler
+            .get_shift_stats([7, 15, 23])
+            .into_iter()
+            .map(|(d, s, y)| (d, s, y.panels.0))
+            .collect();
+        shifts.sort();
+
+        let last_year = chrono::NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+        assert_eq!(shifts, vec![(last_year, 3, 1), (date(1), 1, 2), (date(1), 2, 2), (date(1), 3, 1)]);
+
+        // The same logs with the default starts
+        let mut shifts: Vec<(chrono::NaiveDate, u8, u16)> = handler
+            .get_shift_stats(ICT_config::DEFAULT_SHIFT_STARTS)
+            .into_iter()
+            .map(|(d, s, y)| (d, s, y.panels.0))
+            .collect();
+        shifts.sort();
+        assert_eq!(shifts, vec![(date(1), 1, 2), (date(1), 2, 2), (date(1), 3, 2)]);
+    }
+
+    #[test]
+    fn failed_boards_in_range() {
+        let mut handler = LogFileHandler::new();
+        let dmc = "V0L2400001001PRODUCT01";
+
+        // Failed first, passed on the retest an hour later
+        for (result, end) in [(BResult::Fail, 240101120100), (BResult::Pass, 240101130100)] {
+            let mut log = make_log(
+                &format!("1-{dmc}-{end}.log"),
+                dmc,
+                1,
+                vec![make_test("r1", result, 1000.0, TLimit::Lim2(1100.0, 900.0))],
+            );
+            log.time_start = end - 100;
+            log.time_end = end;
+            assert!(handler.push(log));
+        }
+        handler.update();
+
+        let all = handler.get_failed_boards();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].3, vec!["r1".to_string()]);
+
+        assert_eq!(handler.get_failed_boards_in_range(240101120000, 240101125959).len(), 1);
+        assert!(handler.get_failed_boards_in_range(240101130000, 240101135959).is_empty());
+    }
+
+    #[test]
+    fn merge_into_empty_keeps_testlist_growth() {
+        // Logs an hour apart, every log after the first one adds a test
+        let logs = || {
+            let names = ["r1", "r2", "r3", "r4"];
+            (0..4)
+                .map(|i| {
+                    let dmc = format!("V0L240{:07}PRODUCT01", 1001 + i);
+                    let count = if i == 0 { 1 } else { i + 1 };
+                    let tests = names[..count]
+                        .iter()
+                        .map(|n| make_test(n, BResult::Pass, 1000.0, TLimit::Lim2(1100.0, 900.0)))
+                        .collect();
+                    let mut log = make_log(&format!("1-{dmc}.log"), &dmc, 1, tests);
+                    log.time_start = 240101120000 + i as u64 * 10000;
+                    log.time_end = log.time_start + 100;
+                    log
+                })
+                .collect::<Vec<LogFile>>()
+        };
+
+        let mut sequential = LogFileHandler::new();
+        for log in logs() {
+            assert!(sequential.push(log));
+        }
+
+        let mut first = LogFileHandler::new();
+        let mut second = LogFileHandler::new();
+        for (i, log) in logs().into_iter().enumerate() {
+            assert!(if i < 2 { first.push(log) } else { second.push(log) });
+        }
+
+        let mut merged = LogFileHandler::new();
+        merged.merge(first).unwrap();
+        merged.merge(second).unwrap();
+
+        assert_eq!(merged.get_testlist(), sequential.get_testlist());
+        assert_eq!(merged.get_testlist_growth_points(), sequential.get_testlist_growth_points());
+        assert_eq!(
+            merged.get_testlist_growth_points(),
+            vec![(1, 240101130000), (2, 240101140000), (3, 240101150000)]
+        );
+    }
+}
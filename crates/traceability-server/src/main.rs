@@ -282,7 +282,7 @@ pub struct MyLoginWindow {
     btn_login: gui::Button, // a button
 
     users: Vec<User>,
-    selected: Arc<Mutex<Option<usize>>>,
+    selected: Arc<Mutex<Option<User>>>,
 }
 
 impl Default for MyLoginWindow {
@@ -347,8 +347,8 @@ impl MyLoginWindow {
     pub fn run(&self) -> AnyResult<User> {
         self.wnd.run_main(None)?; // simply let the window manager do the hard work
 
-        if let Some(i) = *self.selected.lock().unwrap() {
-            Ok(self.users[i].clone())
+        if let Some(user) = self.selected.lock().unwrap().clone() {
+            Ok(user)
         } else {
             AnyResult::Err("Failed login".into())
         }
@@ -359,12 +359,11 @@ impl MyLoginWindow {
         let self2 = self.clone();
         self2.btn_login.on().bn_clicked(move || {
             // button click event
-            for (i, user) in self2.users.iter().enumerate() {
-                if user.name == self2.edit_name.text() && user.check_pw(&self2.edit_pass.text()) {
-                    *sel_2.lock().unwrap() = Some(i);
-                    self2.wnd.hwnd().DestroyWindow()?;
-                    break;
-                }
+            if let Some(user) =
+                authenticate_user(&self2.users, &self2.edit_name.text(), &self2.edit_pass.text())
+            {
+                *sel_2.lock().unwrap() = Some(user.clone());
+                self2.wnd.hwnd().DestroyWindow()?;
             }
             Ok(())
         });